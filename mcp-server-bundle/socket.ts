@@ -37,7 +37,7 @@ function handleConnection(ws: ServerWebSocket<any>) {
 }
 
 const server = Bun.serve({
-  port: 3055,
+  port: Number(process.env.PORT) || 3055,
   // uncomment this to allow connections in windows wsl
   hostname: "0.0.0.0",
   fetch(req: Request, server: Server) {
@@ -0,0 +1,76 @@
+// Stand-in for the Bun and Python servers used by the process management tests.
+//
+// Binds PORT (0 picks an ephemeral port), prints uvicorn's startup line so the real port
+// can be read back, and answers every request with a health payload. Flags:
+//   --ignore-term      ignore SIGTERM so callers have to escalate to a kill
+//   --unhealthy        report {"status": "starting"} instead of healthy
+//   --exit-after <ms>  exit with status 3 after the given delay
+//   --spawn-worker     fork a child into the same process group, like uvicorn's reloader
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::time::Duration;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let flag = |name: &str| args.iter().any(|arg| arg == name);
+
+    if flag("--worker") {
+        loop {
+            std::thread::sleep(Duration::from_secs(60));
+        }
+    }
+
+    #[cfg(unix)]
+    if flag("--ignore-term") {
+        unsafe {
+            libc::signal(libc::SIGTERM, libc::SIG_IGN);
+        }
+    }
+
+    if let Some(delay) = args
+        .iter()
+        .position(|arg| arg == "--exit-after")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|ms| ms.parse().ok())
+    {
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(delay));
+            std::process::exit(3);
+        });
+    }
+
+    if flag("--spawn-worker") {
+        // The worker only ever dies together with its process group
+        #[allow(clippy::zombie_processes)]
+        let worker = std::process::Command::new(std::env::current_exe().unwrap())
+            .arg("--worker")
+            .spawn()
+            .expect("failed to spawn worker");
+        println!("Started worker process [{}]", worker.id());
+    }
+
+    let port: u16 = std::env::var("PORT").ok().and_then(|p| p.parse().ok()).unwrap_or(0);
+    let listener = TcpListener::bind(("127.0.0.1", port)).expect("failed to bind port");
+    let port = listener.local_addr().unwrap().port();
+    println!("INFO:     Uvicorn running on http://127.0.0.1:{} (Press CTRL+C to quit)", port);
+    std::io::stdout().flush().unwrap();
+
+    let status = if flag("--unhealthy") { "starting" } else { "healthy" };
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else { continue };
+        // Drain the request head; the path doesn't matter
+        let mut reader = BufReader::new(&stream);
+        let mut line = String::new();
+        while reader.read_line(&mut line).map(|n| n > 0).unwrap_or(false) && line != "\r\n" {
+            line.clear();
+        }
+        let body = format!("{{\"status\":\"{}\"}}", status);
+        let _ = write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+    }
+}
@@ -0,0 +1,32 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::Path;
+
+// Settings saved from the app (ports and the rest of ServerConfig), under ~/.wirecraft.
+// Takes precedence over a hand-written config.toml, which stays for deployment images.
+pub const CONFIG_FILE: &str = "config.json";
+
+// Reads the settings file; Ok(None) when it doesn't exist yet
+pub fn load<T: DeserializeOwned>(path: &Path) -> std::io::Result<Option<T>> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+// Writes the settings next to the final path first, so a crash mid-write can't leave a
+// truncated file that resets every setting on the next launch
+pub fn save<T: Serialize>(path: &Path, config: &T) -> std::io::Result<()> {
+    let contents = serde_json::to_string_pretty(config)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, contents)?;
+    std::fs::rename(&tmp_path, path)
+}
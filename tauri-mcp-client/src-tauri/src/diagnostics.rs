@@ -0,0 +1,36 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+// Writes a support archive at `dest`: every file in `logs_dir` under logs/, plus the
+// generated `files` (name, contents) at the archive root. Callers redact secrets first.
+pub fn write_archive(
+    dest: &Path,
+    logs_dir: Option<&Path>,
+    files: &[(&str, String)],
+) -> zip::result::ZipResult<()> {
+    let mut zip = ZipWriter::new(File::create(dest)?);
+    let options = SimpleFileOptions::default();
+
+    if let Some(logs_dir) = logs_dir.filter(|dir| dir.is_dir()) {
+        for entry in std::fs::read_dir(logs_dir)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            let name = format!("logs/{}", entry.file_name().to_string_lossy());
+            zip.start_file(name, options)?;
+            std::io::copy(&mut File::open(entry.path())?, &mut zip)?;
+        }
+    }
+
+    for (name, contents) in files {
+        zip.start_file(*name, options)?;
+        zip.write_all(contents.as_bytes())?;
+    }
+
+    zip.finish()?;
+    Ok(())
+}
@@ -0,0 +1,197 @@
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+// Error returned by every command. Serialized for the frontend as
+// `{ "kind": "port_in_use", "message": "...", ...fields }`.
+#[derive(Debug, Serialize)]
+#[serde(remote = "Self", tag = "kind", rename_all = "snake_case")]
+pub enum AppError {
+    BunNotFound,
+    PythonNotFound,
+    PythonTooOld { found: String, required: String },
+    IncompatibleBun { found: String, required: String },
+    HomeDirNotFound,
+    ServerNotInstalled,
+    ServerRunning,
+    InvalidBundle { path: String, missing: String },
+    ArchMismatch { bundle: String, host: String },
+    AlreadyRunning { pid: u32 },
+    NotFound { what: String },
+    SpawnFailed { process: String, reason: String },
+    CommandFailed { command: String, stderr: String },
+    StartupFailed { server: String, exit_code: Option<i32>, stderr: String },
+    DependenciesMissing { missing: Vec<String> },
+    Io { context: String, reason: String },
+    PermissionDenied { path: String, reason: String },
+    PortInUse { port: u16 },
+    ForeignProcess { port: u16, pid: u32, process: String },
+    InsufficientDiskSpace { required_bytes: u64, available_bytes: u64 },
+    ChecksumMismatch { file: String, expected: String, actual: String },
+    Timeout { operation: String, seconds: u64, stderr: String },
+    HealthCheckFailed { reason: String },
+    Offline { action: String },
+    OperationCancelled,
+    Busy { operation: String },
+    InvalidArgument { reason: String },
+}
+
+impl AppError {
+    pub fn io(context: impl Into<String>, err: impl fmt::Display) -> Self {
+        AppError::Io {
+            context: context.into(),
+            reason: err.to_string(),
+        }
+    }
+
+    pub fn spawn(process: impl Into<String>, err: impl fmt::Display) -> Self {
+        AppError::SpawnFailed {
+            process: process.into(),
+            reason: err.to_string(),
+        }
+    }
+
+    pub fn not_found(what: impl Into<String>) -> Self {
+        AppError::NotFound { what: what.into() }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::BunNotFound => write!(f, "Bun executable not found"),
+            AppError::PythonNotFound => write!(f, "Python executable not found"),
+            AppError::PythonTooOld { found, required } => write!(
+                f,
+                "Python {}+ is required, but the newest interpreter found is {}",
+                required, found
+            ),
+            AppError::IncompatibleBun { found, required } => write!(
+                f,
+                "Bun {}+ is required to run the MCP server, but {} is installed. \
+                 Upgrade it with install_bun.",
+                required, found
+            ),
+            AppError::HomeDirNotFound => write!(f, "Failed to get home directory"),
+            AppError::ServerNotInstalled => {
+                write!(f, "MCP server not installed. Please install it first.")
+            }
+            AppError::ServerRunning => write!(
+                f,
+                "MCP server is running. Stop it first or pass force to stop it automatically."
+            ),
+            AppError::InvalidBundle { path, missing } => {
+                write!(f, "Invalid bundle at {}: missing {}", path, missing)
+            }
+            AppError::ArchMismatch { bundle, host } => write!(
+                f,
+                "MCP server bundle was built for {}, which does not match this machine ({})",
+                bundle, host
+            ),
+            AppError::AlreadyRunning { pid } => {
+                write!(f, "MCP server is already running (PID {})", pid)
+            }
+            AppError::NotFound { what } => write!(f, "{} not found", what),
+            AppError::SpawnFailed { process, reason } => {
+                write!(f, "Failed to start {}: {}", process, reason)
+            }
+            AppError::CommandFailed { command, stderr } => {
+                write!(f, "{} failed: {}", command, stderr)
+            }
+            AppError::StartupFailed {
+                server,
+                exit_code,
+                stderr,
+            } => {
+                match exit_code {
+                    Some(code) => write!(f, "{} exited during startup with code {}", server, code)?,
+                    None => write!(f, "{} exited during startup", server)?,
+                }
+                if !stderr.is_empty() {
+                    write!(f, ":\n{}", stderr)?;
+                }
+                Ok(())
+            }
+            AppError::DependenciesMissing { missing } => write!(
+                f,
+                "bun install finished but these dependencies are missing: {}",
+                missing.join(", ")
+            ),
+            AppError::Io { context, reason } => write!(f, "{}: {}", context, reason),
+            AppError::PermissionDenied { path, reason } => write!(
+                f,
+                "Cannot write to {} ({}). Set WIRECRAFT_HOME to a writable directory, \
+                 or enable install_dir_fallback in the server config.",
+                path, reason
+            ),
+            AppError::PortInUse { port } => write!(f, "Port {} is already in use", port),
+            AppError::ForeignProcess { port, pid, process } => write!(
+                f,
+                "Port {} is held by {} (PID {}), which this app did not start; refusing to stop it",
+                port, process, pid
+            ),
+            AppError::InsufficientDiskSpace {
+                required_bytes,
+                available_bytes,
+            } => write!(
+                f,
+                "Not enough disk space: {} bytes required, {} bytes available",
+                required_bytes, available_bytes
+            ),
+            AppError::ChecksumMismatch {
+                file,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Checksum mismatch for {}: expected SHA-256 {}, got {}",
+                file, expected, actual
+            ),
+            AppError::Timeout {
+                operation,
+                seconds,
+                stderr,
+            } => {
+                write!(f, "{} timed out after {}s", operation, seconds)?;
+                if !stderr.is_empty() {
+                    write!(f, ": {}", stderr)?;
+                }
+                Ok(())
+            }
+            AppError::Offline { action } => write!(
+                f,
+                "{} requires network access, but offline mode is enabled",
+                action
+            ),
+            AppError::OperationCancelled => write!(f, "Operation cancelled"),
+            AppError::Busy { operation } => write!(f, "{} is already in progress", operation),
+            AppError::HealthCheckFailed { reason } => write!(f, "Health check failed: {}", reason),
+            AppError::InvalidArgument { reason } => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Tagged<'a> {
+            #[serde(flatten, serialize_with = "serialize_fields")]
+            error: &'a AppError,
+            message: String,
+        }
+
+        fn serialize_fields<S: Serializer>(
+            error: &&AppError,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            AppError::serialize(error, serializer)
+        }
+
+        Tagged {
+            error: self,
+            message: self.to_string(),
+        }
+        .serialize(serializer)
+    }
+}
@@ -1,21 +1,78 @@
-use std::process::Command;
-use tauri::Manager;
+use std::process::{Command, Stdio};
+use tauri::{Emitter, Manager};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 use std::sync::{Arc, Mutex};
 use std::process::Child;
+use tokio_util::sync::CancellationToken;
+
+mod config;
+mod diagnostics;
+mod error;
+mod log_writer;
+mod manifest;
+pub mod process;
+
+pub use error::AppError;
+use log_writer::LogWriter;
+use manifest::ManifestVerification;
+use process::{
+    configure_process_group, find_listening_pid, kill_process_group, port_available, process_alive,
+    terminate_child, terminate_process_group,
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BunStatus {
     pub installed: bool,
     pub version: Option<String>,
     pub path: Option<String>,
+    // The version is known and at least MIN_BUN
+    pub compatible: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PythonStatus {
+    pub installed: bool,
+    pub version: Option<String>,
+    pub path: Option<String>,
+}
+
+// A runtime's version: `version` is set when `raw` parses as SemVer
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RuntimeVersion {
+    pub raw: Option<String>,
+    pub version: Option<semver::Version>,
+    pub compatible: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RuntimeVersions {
+    pub bun: RuntimeVersion,
+    pub python: RuntimeVersion,
+    // Both runtimes meet the minimum supported versions
+    pub compatible: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct McpServerStatus {
+    // True only once the socket server answers HTTP, not merely when the port is bound
     pub running: bool,
+    pub port_open: bool,
     pub port: Option<u16>,
+    // Whether the socket relay on socket_port answers, probed separately from `running`
+    pub socket_running: bool,
+    // Whether the listener on `port` is one of this app's processes rather than another
+    // program (or another app instance) squatting the port
+    pub owned_by_us: bool,
     pub pid: Option<u32>,
+    pub restart_count: u32,
+    pub last_exit_code: Option<i32>,
+    pub mode: ServerMode,
+    // Running with settings older than the current ServerConfig; restart to apply them
+    pub config_dirty: bool,
+    // Unix seconds when the running process was started
+    pub started_at: Option<u64>,
+    pub uptime_secs: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -24,502 +81,5574 @@ pub struct FastAPIStatus {
     pub port: Option<u16>,
     pub pid: Option<u32>,
     pub health_check_url: Option<String>,
+    pub restart_count: u32,
+    pub last_exit_code: Option<i32>,
+    // Tail of stderr from the last run that exited
+    pub last_error: Option<String>,
+    pub mode: ServerMode,
+    // Running with settings older than the current ServerConfig; restart to apply them
+    pub config_dirty: bool,
+    // Unix seconds when the running process was started
+    pub started_at: Option<u64>,
+    pub uptime_secs: Option<u64>,
+    // Address uvicorn binds; exposure_warning is set while it listens beyond loopback
+    pub host: String,
+    pub exposure_warning: Option<String>,
 }
 
-// Global state for FastAPI process
-type FastAPIProcess = Arc<Mutex<Option<Child>>>;
+// Payload of the process://restarted and process://restart-failed events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerRestartEvent {
+    pub server: String,
+    pub attempt: u32,
+    pub exit_code: Option<i32>,
+}
 
-// Helper function to get Bun executable path
-fn get_bun_path() -> Result<String, String> {
-    // First try to find bun in PATH
-    if let Ok(bun_path) = which::which("bun") {
-        return Ok(bun_path.to_string_lossy().to_string());
-    }
-    
-    // If not in PATH, check common installation locations
-    let home_dir = std::env::var("HOME").unwrap_or_default();
-    let bun_home_path = format!("{}/.bun/bin/bun", home_dir);
-    
-    if std::path::Path::new(&bun_home_path).exists() {
-        return Ok(bun_home_path);
-    }
-    
-    Err("Bun executable not found".to_string())
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticStatus {
+    Ok,
+    Warn,
+    Fail,
 }
 
-// Helper function to get Python executable path
-fn get_python_path() -> Result<String, String> {
-    // Try python3.11 first (preferred), then python3, then python
-    for python_cmd in &["python3.11", "python3", "python"] {
-        if let Ok(python_path) = which::which(python_cmd) {
-            // Verify it's a compatible version
-            if let Ok(output) = Command::new(&python_path).arg("--version").output() {
-                if output.status.success() {
-                    let version_str = String::from_utf8_lossy(&output.stdout);
-                    log::info!("Found Python: {} ({})", python_path.display(), version_str.trim());
-                    return Ok(python_path.to_string_lossy().to_string());
-                }
-            }
+// One check of diagnose; suggested_fix names the command or action that resolves it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub check: String,
+    pub status: DiagnosticStatus,
+    pub detail: String,
+    pub suggested_fix: Option<String>,
+}
+
+impl Diagnostic {
+    fn ok(check: &str, detail: impl Into<String>) -> Self {
+        Diagnostic {
+            check: check.to_string(),
+            status: DiagnosticStatus::Ok,
+            detail: detail.into(),
+            suggested_fix: None,
         }
     }
-    
-    Err("Python executable not found".to_string())
-}
 
-#[tauri::command]
-async fn check_bun_installation() -> Result<BunStatus, String> {
-    match get_bun_path() {
-        Ok(bun_path) => {
-            // Try to get version
-            if let Ok(output) = Command::new(&bun_path).arg("--version").output() {
-                if output.status.success() {
-                    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                    return Ok(BunStatus {
-                        installed: true,
-                        version: Some(version),
-                        path: Some(bun_path),
-                    });
-                }
-            }
-            
-            // Bun found but can't get version
-            Ok(BunStatus {
-                installed: true,
-                version: None,
-                path: Some(bun_path),
-            })
+    fn warn(check: &str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Diagnostic {
+            status: DiagnosticStatus::Warn,
+            suggested_fix: Some(fix.into()),
+            ..Diagnostic::ok(check, detail)
         }
-        Err(_) => {
-            // Bun not found
-            Ok(BunStatus {
-                installed: false,
-                version: None,
-                path: None,
-            })
+    }
+
+    fn fail(check: &str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Diagnostic {
+            status: DiagnosticStatus::Fail,
+            suggested_fix: Some(fix.into()),
+            ..Diagnostic::ok(check, detail)
         }
     }
 }
 
-#[tauri::command]
-async fn install_bun() -> Result<String, String> {
-    let install_script = if cfg!(target_os = "windows") {
-        "powershell -c \"irm bun.sh/install.ps1 | iex\""
-    } else {
-        "curl -fsSL https://bun.sh/install | bash"
-    };
+// Payload of the `server-unhealthy` / `server-recovered` events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerHealthEvent {
+    pub server: String,
+    pub consecutive_failures: u32,
+    pub error: Option<String>,
+}
 
-    match Command::new("sh").arg("-c").arg(install_script).output() {
-        Ok(output) => {
-            if output.status.success() {
-                Ok("Bun installed successfully".to_string())
-            } else {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                Err(format!("Failed to install Bun: {}", stderr))
-            }
-        }
-        Err(e) => Err(format!("Error executing install command: {}", e)),
-    }
+// Everything the dashboard shows, gathered in a single command
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SystemStatus {
+    pub bun: BunStatus,
+    pub python: PythonStatus,
+    pub mcp_server: McpServerStatus,
+    pub fastapi: FastAPIStatus,
 }
 
-#[tauri::command]
-async fn install_mcp_server(app_handle: tauri::AppHandle) -> Result<String, String> {
-    let home_dir = std::env::var("HOME")
-        .map_err(|_| "Failed to get home directory".to_string())?;
-    
-    let wirecraft_dir = std::path::Path::new(&home_dir).join(".wirecraft");
-    let mcp_server_dir = wirecraft_dir.join("mcp-server");
-    
-    // Create ~/.wirecraft directory if it doesn't exist
-    std::fs::create_dir_all(&mcp_server_dir)
-        .map_err(|e| format!("Failed to create ~/.wirecraft/mcp-server directory: {}", e))?;
+// Result of check_mcp_socket_link
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SocketLinkCheck {
+    pub port: u16,
+    pub success: bool,
+    // Time from connecting to receiving the join acknowledgement
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
 
-    // Get the bundled MCP server files - check multiple possible locations
-    let mut bundled_server_dir = None;
-    
-    // First try the resource directory (production)
-    if let Ok(resource_dir) = app_handle.path().resource_dir() {
-        log::info!("Resource directory: {:?}", resource_dir);
-        
-        // Check direct path first
-        let candidate = resource_dir.join("mcp-server-bundle");
-        log::info!("Checking direct resource candidate: {:?}", candidate);
-        if candidate.exists() {
-            bundled_server_dir = Some(candidate);
-        } else {
-            // Check the _up_/_up_ path structure we see in the bundle
-            let up_candidate = resource_dir.join("_up_").join("_up_").join("mcp-server-bundle");
-            log::info!("Checking _up_/_up_ candidate: {:?}", up_candidate);
-            if up_candidate.exists() {
-                bundled_server_dir = Some(up_candidate);
-            }
-        }
-    }
-    
-    // If not found, try development path (relative to project root)
-    if bundled_server_dir.is_none() {
-        let current_dir = std::env::current_dir()
-            .map_err(|e| format!("Failed to get current directory: {}", e))?;
-        
-        log::info!("Current directory: {:?}", current_dir);
-        
-        // Try relative to project root (go up from src-tauri to tauri-mcp-client, then up to main project)
-        let dev_candidate = current_dir.parent() // from src-tauri to tauri-mcp-client
-            .and_then(|p| p.parent()) // from tauri-mcp-client to cursor-talk-to-figma-mcp
-            .ok_or("Could not find project root directory")?
-            .join("mcp-server-bundle");
-            
-        log::info!("Checking dev candidate: {:?}", dev_candidate);
-            
-        if dev_candidate.exists() {
-            log::info!("Found MCP server bundle at: {:?}", dev_candidate);
-            bundled_server_dir = Some(dev_candidate);
-        }
-    }
-    
-    // Copy MCP server files to ~/.wirecraft/mcp-server
-    match bundled_server_dir {
-        Some(source_dir) => {
-            copy_dir_recursive(&source_dir, &mcp_server_dir)
-                .map_err(|e| format!("Failed to copy MCP server files: {}", e))?;
-        }
-        None => {
-            return Err("MCP server bundle not found in resources or development path".to_string());
-        }
-    }
+// Result of get_ports_in_use; pid and process_name are best-effort platform lookups
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PortInfo {
+    pub port: u16,
+    pub occupied: bool,
+    pub pid: Option<u32>,
+    pub process_name: Option<String>,
+}
 
-    // Install dependencies
-    let bun_path = get_bun_path()
-        .map_err(|e| format!("Bun not found for dependency installation: {}", e))?;
-    let install_output = Command::new(&bun_path)
-        .args(&["install"])
-        .current_dir(&mcp_server_dir)
-        .output()
-        .map_err(|e| format!("Failed to run bun install: {}", e))?;
+// One entry of get_process_metrics. memory_bytes and cpu_percent are None once the
+// process has exited; cpu_percent is relative to one core, so it can exceed 100.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProcMetric {
+    pub name: String,
+    pub pid: Option<u32>,
+    pub running: bool,
+    pub memory_bytes: Option<u64>,
+    pub cpu_percent: Option<f32>,
+}
 
-    if !install_output.status.success() {
-        let stderr = String::from_utf8_lossy(&install_output.stderr);
-        return Err(format!("Failed to install MCP server dependencies: {}", stderr));
-    }
+// Payload of the `fastapi-ready` / `fastapi-failed` events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FastAPIReadinessEvent {
+    pub port: u16,
+    pub pid: u32,
+    pub error: Option<String>,
+}
 
-    Ok("MCP Server installed successfully to ~/.wirecraft/mcp-server".to_string())
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessLogLine {
+    pub stream: String,
+    pub line: String,
 }
 
-#[tauri::command]
-async fn start_mcp_server() -> Result<String, String> {
-    let home_dir = std::env::var("HOME")
-        .map_err(|_| "Failed to get home directory".to_string())?;
-    
-    let mcp_server_dir = std::path::Path::new(&home_dir).join(".wirecraft").join("mcp-server");
-    
-    if !mcp_server_dir.exists() {
-        return Err("MCP server not installed. Please install it first.".to_string());
-    }
+// Result of check_fastapi_health: the /health status, round-trip time and the full body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub status: String,
+    pub latency_ms: u64,
+    pub details: serde_json::Value,
+}
 
-    // Get Bun path
-    let bun_path = get_bun_path()
-        .map_err(|e| format!("Bun not found for starting MCP server: {}", e))?;
+// Payload of `log://line` events: the lines a process printed since the previous event,
+// tagged with the process they came from, plus how many were dropped in between
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessLogEvent {
+    pub process_id: String,
+    pub lines: Vec<ProcessLogLine>,
+    pub dropped: u64,
+}
 
-    // Start the MCP server
-    let server_path = mcp_server_dir.join("server.ts");
-    let _child = Command::new(&bun_path)
-        .args(&["run", server_path.to_str().unwrap()])
-        .current_dir(&mcp_server_dir)
-        .spawn()
-        .map_err(|e| format!("Failed to start MCP server: {}", e))?;
+// Payload of `<server>-log` events: the lines captured since the previous event, plus how
+// many were dropped because the frontend fell behind
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessLogBatch {
+    pub lines: Vec<ProcessLogLine>,
+    pub dropped: u64,
+}
 
-    // Store the child process ID for later management
-    let pid = _child.id();
-    
-    // Start socket server as well
-    let socket_path = mcp_server_dir.join("socket.ts");
-    let _socket_child = Command::new(&bun_path)
-        .args(&["run", socket_path.to_str().unwrap()])
-        .current_dir(&mcp_server_dir)
-        .spawn()
-        .map_err(|e| format!("Failed to start socket server: {}", e))?;
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallPhase {
+    CopyingFiles,
+    InstallingDependencies,
+    Done,
+}
 
-    Ok(format!("MCP Server started with PID: {}", pid))
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupPhase {
+    WaitingForMcp,
+    Starting,
 }
 
-#[tauri::command]
-async fn check_mcp_server_installation() -> Result<bool, String> {
-    let home_dir = std::env::var("HOME")
-        .map_err(|_| "Failed to get home directory".to_string())?;
-    
-    let mcp_server_dir = std::path::Path::new(&home_dir).join(".wirecraft").join("mcp-server");
-    let server_file = mcp_server_dir.join("server.ts");
-    let package_file = mcp_server_dir.join("package.json");
-    
-    Ok(server_file.exists() && package_file.exists())
+// Payload of the `startup-phase` event, emitted as a server moves through ordered startup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartupPhaseEvent {
+    pub server: String,
+    pub phase: StartupPhase,
 }
 
-// Helper function to copy directories recursively
-fn copy_dir_recursive(src: &std::path::Path, dst: &std::path::Path) -> std::io::Result<()> {
-    if !dst.exists() {
-        std::fs::create_dir_all(dst)?;
+// Payload of the `mcp-install-progress` event; file counts are only set while copying,
+// and package counts and output lines only while bun install runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallProgress {
+    pub phase: InstallPhase,
+    pub files_copied: Option<u64>,
+    pub total_files: Option<u64>,
+    pub packages: Option<u64>,
+    pub output_line: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum ToolchainDiagnostic {
+    ToolchainIsShim {
+        tool: String,
+        manager: String,
+        path: String,
+        resolved_path: Option<String>,
+    },
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProvisionOptions {
+    // Reinstall the MCP server even when an install is already present
+    pub reinstall_mcp_server: bool,
+    // Leave the FastAPI server stopped
+    pub skip_fastapi: bool,
+    // Don't install Bun or the MCP server; use only what is already there
+    pub skip_install: bool,
+    // Leave both servers stopped
+    pub skip_start: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepStatus {
+    Succeeded,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StepResult {
+    pub name: String,
+    pub status: StepStatus,
+    pub duration_ms: u64,
+    pub detail: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ProvisionReport {
+    pub success: bool,
+    pub steps: Vec<StepResult>,
+}
+
+// Outcome of the default bootstrap run by setup_environment
+pub type SetupReport = ProvisionReport;
+
+// Outcome of stop_all, one step per service
+pub type StopAllReport = ProvisionReport;
+
+impl ProvisionReport {
+    fn skip(&mut self, name: &str, detail: impl Into<String>) {
+        self.steps.push(StepResult {
+            name: name.to_string(),
+            status: StepStatus::Skipped,
+            duration_ms: 0,
+            detail: detail.into(),
+        });
     }
-    
-    for entry in std::fs::read_dir(src)? {
-        let entry = entry?;
-        let file_type = entry.file_type()?;
-        let src_path = entry.path();
-        let dst_path = dst.join(entry.file_name());
-        
-        if file_type.is_dir() {
-            copy_dir_recursive(&src_path, &dst_path)?;
-        } else {
-            std::fs::copy(&src_path, &dst_path)?;
-        }
+
+    async fn run(
+        &mut self,
+        name: &str,
+        step: impl std::future::Future<Output = Result<String, AppError>>,
+    ) -> bool {
+        let started = std::time::Instant::now();
+        let result = step.await;
+        let duration_ms = started.elapsed().as_millis() as u64;
+
+        let (status, detail, succeeded) = match result {
+            Ok(detail) => (StepStatus::Succeeded, detail, true),
+            Err(e) => (StepStatus::Failed, e.to_string(), false),
+        };
+        self.steps.push(StepResult {
+            name: name.to_string(),
+            status,
+            duration_ms,
+            detail,
+        });
+        succeeded
     }
-    
-    Ok(())
 }
 
-#[tauri::command]
-async fn get_mcp_server_status() -> Result<McpServerStatus, String> {
-    // Simple check to see if the server is running on default port
-    match std::net::TcpStream::connect("127.0.0.1:3055") {
-        Ok(_) => Ok(McpServerStatus {
-            running: true,
-            port: Some(3055),
-            pid: None, // We'd need to store this somewhere to track it
-        }),
-        Err(_) => Ok(McpServerStatus {
-            running: false,
-            port: None,
-            pid: None,
+// Oldest Python the FastAPI app supports, as (major, minor)
+const MIN_PYTHON: (u32, u32) = (3, 10);
+
+// Oldest Bun the bundled MCP server is tested against
+const MIN_BUN: (u64, u64, u64) = (1, 0, 0);
+
+fn min_bun() -> semver::Version {
+    semver::Version::new(MIN_BUN.0, MIN_BUN.1, MIN_BUN.2)
+}
+
+// Helper function to refuse a Bun too old to run server.ts. A version that can't be read
+// or parsed is let through, since the start itself will report a broken binary.
+fn check_bun_compatible(bun_path: &str) -> Result<(), AppError> {
+    let Ok(output) = Command::new(bun_path).arg("--version").output() else {
+        return Ok(());
+    };
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    match parse_semver(&version) {
+        Some(parsed) if parsed < min_bun() => Err(AppError::IncompatibleBun {
+            found: version,
+            required: min_bun().to_string(),
         }),
+        Some(_) => Ok(()),
+        None => {
+            log::warn!("Could not parse Bun version {:?}; starting anyway", version);
+            Ok(())
+        }
     }
 }
 
-// FastAPI Server Management Functions
+// Version managers whose shim directories re-exec the real binary on every call
+const SHIM_MANAGERS: &[(&str, &str)] = &[
+    ("volta", ".volta/bin"),
+    ("pyenv", ".pyenv/shims"),
+    ("asdf", ".asdf/shims"),
+    ("mise", ".local/share/mise/shims"),
+];
 
-#[tauri::command]
-async fn start_fastapi_server(app_handle: tauri::AppHandle) -> Result<String, String> {
-    let fastapi_process: FastAPIProcess = app_handle.state::<FastAPIProcess>().inner().clone();
-    
-    // Check if already running
-    {
-        let mut process = fastapi_process.lock().unwrap();
-        if let Some(child) = process.as_mut() {
-            if let Ok(None) = child.try_wait() {
-                return Ok("FastAPI server is already running".to_string());
-            }
-        }
-    }
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServerMode {
+    Development,
+    #[default]
+    Production,
+}
 
-    // Find the FastAPI directory - try multiple locations
-    let mut fastapi_dir = None;
-    
-    // First try getting from current working directory (development)
-    if let Ok(current_dir) = std::env::current_dir() {
-        log::info!("Current directory: {:?}", current_dir);
-        
-        // Try relative path from src-tauri directory
-        let dev_fastapi_dir = current_dir
-            .parent() // from src-tauri to tauri-mcp-client
-            .map(|p| p.join("resource/mcp-client-python/api"));
-            
-        if let Some(dir) = dev_fastapi_dir {
-            log::info!("Checking dev FastAPI dir: {:?}", dir);
-            if dir.exists() {
-                fastapi_dir = Some(dir);
-            }
+impl ServerMode {
+    // Value passed to the servers as NODE_ENV / ENV
+    fn env_value(self) -> &'static str {
+        match self {
+            ServerMode::Development => "development",
+            ServerMode::Production => "production",
         }
     }
-    
-    // If not found in dev, try resource directory (production)
-    if fastapi_dir.is_none() {
-        if let Ok(resource_dir) = app_handle.path().resource_dir() {
-            log::info!("Resource directory: {:?}", resource_dir);
-            let resource_fastapi_dir = resource_dir
-                .parent()
-                .map(|p| p.join("resource/mcp-client-python/api"));
-                
-            if let Some(dir) = resource_fastapi_dir {
-                log::info!("Checking resource FastAPI dir: {:?}", dir);
-                if dir.exists() {
-                    fastapi_dir = Some(dir);
-                }
-            }
+}
+
+// How install copies treat symlinks in the bundle. Symlinked directories are never
+// recursed into, so a link back up the tree can't make the copy loop or escape the bundle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymlinkPolicy {
+    #[default]
+    Skip,
+    // Recreate the link itself, with its original target, in the install
+    Preserve,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub mcp_port: u16,
+    pub socket_port: u16,
+    pub fastapi_port: u16,
+    // Address uvicorn binds. 0.0.0.0 allows remote access; health checks still go over loopback.
+    pub fastapi_host: String,
+    // Where the MCP server is installed when a command doesn't pass one; defaults to
+    // ~/.wirecraft/mcp-server
+    pub install_dir: Option<String>,
+    // When the install directory isn't writable, install under the OS data (or temp)
+    // directory instead of failing
+    pub install_dir_fallback: bool,
+    // Full health endpoint (scheme, host, port, path) for deployments mounted under a
+    // path prefix. Defaults to http://<fastapi_host>:<fastapi_port>/health over loopback.
+    pub health_check_url: Option<String>,
+    // Server log files under ~/.wirecraft/logs are rotated past this size
+    pub max_log_bytes: u64,
+    // How many consecutive crash restarts the supervisor attempts before giving up
+    pub max_restart_retries: u32,
+    // The watchdog checks running servers this often, and reports one as unhealthy after
+    // this many consecutive failed checks
+    pub health_check_interval_secs: u64,
+    pub unhealthy_threshold: u32,
+    // How long uvicorn gets to shut down after SIGTERM before it is killed
+    pub fastapi_shutdown_grace_secs: u64,
+    // Development runs FastAPI with auto-reload and the MCP servers with NODE_ENV=development
+    pub mode: ServerMode,
+    // Air-gapped mode: never download Bun, and install Python packages only from the wheelhouse.
+    // Also enabled by setting the OFFLINE environment variable.
+    pub offline: bool,
+    // Directory of pre-built wheels for offline pip installs, covering requirements.txt,
+    // the parent package and its build backend. Defaults to ~/.wirecraft/wheelhouse.
+    pub wheelhouse_dir: Option<String>,
+    // Interpreter used for the FastAPI venv instead of searching PATH, for machines with
+    // several Pythons installed
+    pub python_path: Option<String>,
+    pub bundle_symlinks: SymlinkPolicy,
+    // Queue a second install, update or uninstall behind the running one instead of
+    // rejecting it as Busy
+    pub wait_for_install_lock: bool,
+    // Hold FastAPI back until the MCP server reports ready, for FastAPI apps whose MCP
+    // client connects at startup
+    pub fastapi_depends_on_mcp: bool,
+    // SHA-256 the downloaded Bun installer must match when install_bun verifies it.
+    // Overrides the checksum pinned at build time or shipped for BUN_VERSION.
+    pub bun_installer_sha256: Option<String>,
+    // Registry `bun install` resolves packages from, e.g. a corporate mirror, and the
+    // token it authenticates with. Written to a temporary .npmrc for each install.
+    pub bun_registry: Option<String>,
+    pub bun_registry_token: Option<String>,
+    // What the launch bootstrap does: install missing pieces, and start both servers.
+    // With auto_start off the app comes up with the servers stopped.
+    pub auto_install: bool,
+    pub auto_start: bool,
+    // Extra arguments appended after `bun run server.ts` and `python main.py`, e.g. debug
+    // flags. Passed straight to the process without a shell; ports, host and reload are
+    // set by the app and can't be overridden here.
+    pub mcp_args: Vec<String>,
+    // server.ts reloads itself on SIGHUP, so reload_mcp_server can signal it instead of
+    // respawning it (Unix only)
+    pub mcp_reload_signal: bool,
+    pub fastapi_args: Vec<String>,
+    // Extra environment variables (API keys, feature flags) for every spawned server.
+    // Applied over the inherited environment; the app's own port variables still win.
+    pub env: HashMap<String, String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        // The bundled MCP server talks stdio and reaches Figma through the
+        // socket server, so both MCP ports point at the socket relay by default
+        Self {
+            mcp_port: 3055,
+            socket_port: 3055,
+            fastapi_port: 8000,
+            fastapi_host: "127.0.0.1".to_string(),
+            install_dir: None,
+            install_dir_fallback: false,
+            health_check_url: None,
+            max_log_bytes: log_writer::DEFAULT_MAX_LOG_BYTES,
+            max_restart_retries: 5,
+            health_check_interval_secs: 10,
+            unhealthy_threshold: 3,
+            fastapi_shutdown_grace_secs: 10,
+            mode: ServerMode::Production,
+            bundle_symlinks: SymlinkPolicy::Skip,
+            wait_for_install_lock: false,
+            fastapi_depends_on_mcp: false,
+            mcp_args: Vec::new(),
+            mcp_reload_signal: false,
+            fastapi_args: Vec::new(),
+            bun_installer_sha256: None,
+            bun_registry: None,
+            bun_registry_token: None,
+            auto_install: true,
+            auto_start: true,
+            offline: false,
+            wheelhouse_dir: None,
+            python_path: None,
+            env: HashMap::new(),
         }
     }
+}
 
-    let fastapi_dir = fastapi_dir.ok_or("FastAPI directory not found. Expected at resource/mcp-client-python/api")?;
-    log::info!("Using FastAPI directory: {:?}", fastapi_dir);
+// Placeholder shown instead of secret env values; sending it back keeps the stored value
+const REDACTED: &str = "********";
 
-    // Check if requirements.txt exists
-    let requirements_file = fastapi_dir.join("requirements.txt");
-    if !requirements_file.exists() {
-        return Err("requirements.txt not found in FastAPI directory".to_string());
-    }
+// Env var names containing any of these are treated as secrets
+const SECRET_ENV_MARKERS: &[&str] = &["KEY", "TOKEN", "SECRET", "PASSWORD", "AUTH", "CREDENTIAL"];
 
-    // Get Python path
-    let python_path = get_python_path()?;
-    log::info!("Using Python: {}", python_path);
+fn is_secret_env(name: &str) -> bool {
+    let name = name.to_uppercase();
+    SECRET_ENV_MARKERS.iter().any(|marker| name.contains(marker))
+}
 
-    // Create virtual environment if it doesn't exist
-    let venv_dir = fastapi_dir.join("venv");
-    if !venv_dir.exists() {
-        log::info!("Creating Python virtual environment...");
-        let output = Command::new(&python_path)
-            .args(&["-m", "venv", "venv"])
-            .current_dir(&fastapi_dir)
-            .output()
-            .map_err(|e| format!("Failed to create virtual environment: {}", e))?;
+// Helper function to mask secret values before env vars leave the backend or hit the log
+fn redact_env(env: &HashMap<String, String>) -> HashMap<String, String> {
+    env.iter()
+        .map(|(name, value)| {
+            let value = if is_secret_env(name) { REDACTED.to_string() } else { value.clone() };
+            (name.clone(), value)
+        })
+        .collect()
+}
 
-        if !output.status.success() {
-            return Err(format!("Failed to create virtual environment: {}", 
-                String::from_utf8_lossy(&output.stderr)));
+// Helper function to apply incoming env vars, keeping stored secrets the caller only saw masked
+fn merge_env(
+    current: &HashMap<String, String>,
+    incoming: HashMap<String, String>,
+) -> HashMap<String, String> {
+    incoming
+        .into_iter()
+        .map(|(name, value)| {
+            let value = match current.get(&name) {
+                Some(existing) if value == REDACTED => existing.clone(),
+                _ => value,
+            };
+            (name, value)
+        })
+        .collect()
+}
+
+impl ServerConfig {
+    fn redacted(&self) -> ServerConfig {
+        ServerConfig {
+            env: redact_env(&self.env),
+            bun_registry_token: self.bun_registry_token.as_ref().map(|_| REDACTED.to_string()),
+            ..self.clone()
         }
-        log::info!("Virtual environment created successfully");
-    } else {
-        log::info!("Virtual environment already exists");
     }
 
-    // Get the Python executable from the virtual environment
-    let venv_python = if cfg!(windows) {
-        venv_dir.join("Scripts/python.exe")
-    } else {
-        venv_dir.join("bin/python")
-    };
-
-    if !venv_python.exists() {
-        return Err(format!("Virtual environment Python not found at: {:?}", venv_python));
+    fn offline(&self) -> bool {
+        self.offline
+            || std::env::var("OFFLINE")
+                .is_ok_and(|value| !matches!(value.as_str(), "" | "0" | "false"))
     }
 
-    // Install dependencies
-    log::info!("Installing FastAPI dependencies...");
-    let pip_install = Command::new(&venv_python)
-        .args(&["-m", "pip", "install", "-r", "requirements.txt"])
-        .current_dir(&fastapi_dir)
-        .output()
-        .map_err(|e| format!("Failed to install dependencies: {}", e))?;
+    fn wheelhouse_dir(&self) -> Result<std::path::PathBuf, AppError> {
+        match &self.wheelhouse_dir {
+            Some(dir) if !dir.is_empty() => Ok(std::path::PathBuf::from(dir)),
+            _ => Ok(wirecraft_dir()?.join("wheelhouse")),
+        }
+    }
 
-    if !pip_install.status.success() {
-        log::warn!("Pip install had issues: {}", String::from_utf8_lossy(&pip_install.stderr));
-        // Don't fail here, continue to try starting the server
-    } else {
-        log::info!("Dependencies installed successfully");
+    // `port` is the port FastAPI actually bound, which differs from fastapi_port when it is 0
+    fn fastapi_health_url(&self, port: u16) -> String {
+        self.health_check_url
+            .clone()
+            .unwrap_or_else(|| format!("http://{}:{}/health", self.fastapi_probe_host(), port))
     }
 
-    // Install the parent package if pyproject.toml exists
-    let parent_dir = fastapi_dir.parent().unwrap();
-    if parent_dir.join("pyproject.toml").exists() {
-        log::info!("Installing parent package...");
-        let parent_install = Command::new(&venv_python)
-            .args(&["-m", "pip", "install", "-e", "."])
-            .current_dir(parent_dir)
-            .output();
-        
-        match parent_install {
-            Ok(output) => {
-                if output.status.success() {
-                    log::info!("Parent package installed successfully");
-                } else {
-                    log::warn!("Parent package install had issues: {}", String::from_utf8_lossy(&output.stderr));
-                }
-            }
-            Err(e) => log::warn!("Failed to install parent package: {}", e),
+    // Where to reach FastAPI from this machine: loopback when it binds every interface
+    fn fastapi_probe_host(&self) -> String {
+        match self.fastapi_host.parse::<std::net::IpAddr>() {
+            Ok(std::net::IpAddr::V4(ip)) if ip.is_unspecified() => "127.0.0.1".to_string(),
+            Ok(std::net::IpAddr::V6(ip)) if ip.is_unspecified() => "[::1]".to_string(),
+            Ok(std::net::IpAddr::V6(ip)) => format!("[{}]", ip),
+            _ => self.fastapi_host.clone(),
         }
     }
 
-    // Check if main.py exists
-    let main_py = fastapi_dir.join("main.py");
-    if !main_py.exists() {
-        return Err("main.py not found in FastAPI directory".to_string());
+    // Whether FastAPI is reachable from other machines with the configured host
+    fn fastapi_host_exposed(&self) -> bool {
+        match self.fastapi_host.parse::<std::net::IpAddr>() {
+            Ok(ip) => !ip.is_loopback(),
+            Err(_) => !self.fastapi_host.eq_ignore_ascii_case("localhost"),
+        }
     }
+}
 
-    // Start the FastAPI server
-    log::info!("Starting FastAPI server...");
-    let mut child = Command::new(&venv_python)
+// Global state for server configuration
+type ServerConfigState = Arc<Mutex<ServerConfig>>;
+
+// Optional file-based configuration, e.g. baked into a deployment image
+fn config_file_path() -> Result<std::path::PathBuf, AppError> {
+    Ok(wirecraft_dir()?.join("config.toml"))
+}
+
+// Settings saved from the app by set_app_config, save_config and set_auto_start
+fn app_config_file_path() -> Result<std::path::PathBuf, AppError> {
+    Ok(wirecraft_dir()?.join(config::CONFIG_FILE))
+}
+
+// Helper function to load ~/.wirecraft/config.json, falling back to config.toml when the
+// app hasn't saved any settings yet. A missing file means defaults; a malformed one is
+// logged and ignored rather than blocking startup.
+fn load_config_file() -> ServerConfig {
+    if let Ok(path) = app_config_file_path() {
+        match config::load::<ServerConfig>(&path) {
+            Ok(Some(config)) => {
+                log::info!("Loaded server configuration from {:?}: {:?}", path, config.redacted());
+                return config;
+            }
+            Ok(None) => {}
+            Err(e) => log::error!("Failed to read {:?}, trying config.toml: {}", path, e),
+        }
+    }
+
+    let Ok(path) = config_file_path() else {
+        return ServerConfig::default();
+    };
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return ServerConfig::default(),
+        Err(e) => {
+            log::error!("Failed to read {:?}, using default configuration: {}", path, e);
+            return ServerConfig::default();
+        }
+    };
+    match toml::from_str::<ServerConfig>(&contents) {
+        Ok(config) => {
+            log::info!("Loaded server configuration from {:?}: {:?}", path, config.redacted());
+            config
+        }
+        Err(e) => {
+            log::error!("Failed to parse {:?}, using default configuration: {}", path, e);
+            ServerConfig::default()
+        }
+    }
+}
+
+// Global state for FastAPI process
+type FastAPIProcess = Arc<Mutex<Option<Child>>>;
+
+// Global state for the MCP server and its companion socket server
+#[derive(Default)]
+struct McpChildren {
+    server: Option<Child>,
+    socket: Option<Child>,
+    // Install directory override the server was last started from, reused on restart
+    install_dir: Option<String>,
+    // Set while a start is in flight so a second call can't spawn a duplicate
+    starting: bool,
+}
+
+// Clears McpChildren::starting when start_mcp_server returns, on success or error
+struct McpStartGuard(McpProcess);
+
+impl Drop for McpStartGuard {
+    fn drop(&mut self) {
+        self.0.lock().unwrap().starting = false;
+    }
+}
+
+type McpProcess = Arc<Mutex<McpChildren>>;
+
+// How many trailing stderr lines are kept per server to explain a crash
+const STDERR_TAIL_LINES: usize = 20;
+
+// Global state for the port uvicorn reported binding, learned from its startup output.
+// Needed when fastapi_port is 0 and the OS picks an ephemeral port.
+type FastAPIBoundPort = Arc<Mutex<Option<u16>>>;
+
+// Helper function to get the port FastAPI is actually listening on
+fn current_fastapi_port(app_handle: &tauri::AppHandle) -> u16 {
+    let bound = *app_handle.state::<FastAPIBoundPort>().lock().unwrap();
+    bound.unwrap_or_else(|| server_config(app_handle).fastapi_port)
+}
+
+// Helper function to read the port from uvicorn's
+// "Uvicorn running on http://0.0.0.0:8000 (Press CTRL+C to quit)" line
+fn parse_uvicorn_port(line: &str) -> Option<u16> {
+    let (_, rest) = line.split_once("Uvicorn running on ")?;
+    let url = rest.split_whitespace().next()?;
+    url.trim_end_matches('/').rsplit(':').next()?.parse().ok()
+}
+
+// Global state for the most recent output of each process, stdout and stderr interleaved,
+// keyed by server name. Kept across restarts so the output of a failed start is still there.
+type ProcessLogs = Arc<Mutex<HashMap<&'static str, VecDeque<ProcessLogLine>>>>;
+
+// How many lines of output are kept in memory per process
+const PROCESS_LOG_LINES: usize = 1000;
+
+// Global state for the most recent stderr lines of each server, keyed by server name
+type StderrTails = Arc<Mutex<HashMap<&'static str, VecDeque<String>>>>;
+
+// How long the Bun installer may run before it is assumed to have stalled
+const BUN_INSTALL_TIMEOUT_SECS: u64 = 120;
+
+// How long a freshly started MCP server and socket server have to come up
+const MCP_READY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+const MCP_READY_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+// How long server.ts must stay alive after spawning before a socket probe counts as ready
+const MCP_STARTUP_CHECK: std::time::Duration = std::time::Duration::from_secs(2);
+// How long FastAPI waits for the MCP server when fastapi_depends_on_mcp is set
+const MCP_DEPENDENCY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+// How long check_mcp_ready waits for the initialize and tools/list round trips
+const MCP_PROBE_TIMEOUT_SECS: u64 = 20;
+// MCP revision the readiness probe speaks; servers answer with the one they support
+const MCP_PROTOCOL_VERSION: &str = "2024-11-05";
+
+// How long a freshly started FastAPI server has to pass its health check
+const FASTAPI_READY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+const FASTAPI_READY_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+// An exit within this window (typically a Python import error) fails the start itself
+const FASTAPI_STARTUP_CHECK: std::time::Duration = std::time::Duration::from_secs(3);
+
+// Global state for the paths the last install and FastAPI start resolved
+type LastResolution = Arc<Mutex<PathResolution>>;
+
+// Helper function to record resolved paths, along with the base directories they were
+// resolved against
+fn record_resolution(app_handle: &tauri::AppHandle, update: impl FnOnce(&mut PathResolution)) {
+    let state = app_handle.state::<LastResolution>();
+    let mut resolution = state.lock().unwrap();
+    resolution.resource_dir = app_handle
+        .path()
+        .resource_dir()
+        .ok()
+        .map(|dir| dir.display().to_string());
+    resolution.current_dir = std::env::current_dir()
+        .ok()
+        .map(|dir| dir.display().to_string());
+    update(&mut resolution);
+}
+
+// Global state for cancelling the in-flight install; each operation gets a fresh token
+type CurrentOperation = Arc<Mutex<CancellationToken>>;
+
+// Held for the whole of every command that changes the MCP server install, so installs,
+// updates and uninstalls can't interleave their copies and bun runs
+type InstallLock = Arc<tokio::sync::Mutex<()>>;
+
+// How long a child gets to exit after SIGTERM before it is killed
+const SHUTDOWN_GRACE_PERIOD: std::time::Duration = std::time::Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ManagedServer {
+    Mcp,
+    FastApi,
+}
+
+impl ManagedServer {
+    fn name(self) -> &'static str {
+        match self {
+            ManagedServer::Mcp => "mcp-server",
+            ManagedServer::FastApi => "fastapi",
+        }
+    }
+}
+
+// Crash-restart bookkeeping for one managed server
+#[derive(Debug, Default)]
+struct RestartState {
+    // Cleared by an explicit stop so the supervisor doesn't resurrect the server
+    desired_running: bool,
+    restart_count: u32,
+    last_exit_code: Option<i32>,
+    last_error: Option<String>,
+    last_restart_at: Option<std::time::Instant>,
+    // The running process was started with a configuration that has since changed
+    config_dirty: bool,
+    // A config-driven restart is in progress; the supervisor stays out of its way
+    restarting: bool,
+    // When the current process was spawned; reset by every (re)start
+    started_at: Option<std::time::SystemTime>,
+    // Consecutive failed watchdog checks, and whether `server-unhealthy` has been emitted
+    health_failures: u32,
+    unhealthy: bool,
+}
+
+impl RestartState {
+    // Start time as Unix seconds and uptime in seconds, for the status structs
+    fn uptime(&self) -> (Option<u64>, Option<u64>) {
+        let Some(started_at) = self.started_at else {
+            return (None, None);
+        };
+        let unix_secs = started_at
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|since| since.as_secs())
+            .ok();
+        let uptime_secs = started_at.elapsed().map(|elapsed| elapsed.as_secs()).unwrap_or(0);
+        (unix_secs, Some(uptime_secs))
+    }
+}
+
+// Global state for the crash supervisor
+#[derive(Debug, Default)]
+struct SupervisorState {
+    mcp: RestartState,
+    fastapi: RestartState,
+}
+
+impl SupervisorState {
+    fn server_mut(&mut self, server: ManagedServer) -> &mut RestartState {
+        match server {
+            ManagedServer::Mcp => &mut self.mcp,
+            ManagedServer::FastApi => &mut self.fastapi,
+        }
+    }
+}
+
+type Supervisor = Arc<Mutex<SupervisorState>>;
+
+const SUPERVISOR_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+const MAX_RESTART_BACKOFF: std::time::Duration = std::time::Duration::from_secs(60);
+
+// Helper function to snapshot the current server configuration
+fn server_config(app_handle: &tauri::AppHandle) -> ServerConfig {
+    app_handle.state::<ServerConfigState>().lock().unwrap().clone()
+}
+
+// Lines captured per server but not yet emitted to the frontend
+#[derive(Default)]
+struct PendingLogLines {
+    lines: Vec<ProcessLogLine>,
+    dropped: u64,
+    open_readers: usize,
+}
+
+// Captured lines are coalesced into one event per interval; past the cap they are dropped
+const LOG_BATCH_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+const MAX_PENDING_LOG_LINES: usize = 1000;
+
+// Helper function to forward a child's stdout/stderr to the frontend as batched
+// `<server>-log` events and persist it to ~/.wirecraft/logs/<server>.log
+fn capture_child_output(app_handle: &tauri::AppHandle, child: &mut Child, server: &'static str) {
+    let max_log_bytes = server_config(app_handle).max_log_bytes;
+    let log_file = log_writer::log_path(server).and_then(|path| {
+        LogWriter::open(path.clone(), max_log_bytes)
+            .map_err(|e| log::warn!("Failed to open log file {:?}: {}", path, e))
+            .ok()
+    });
+    let log_file = Arc::new(Mutex::new(log_file));
+
+    // A fresh process starts with a fresh stderr tail
+    app_handle.state::<StderrTails>().lock().unwrap().remove(server);
+
+    let pending = Arc::new(Mutex::new(PendingLogLines::default()));
+    if let Some(stdout) = child.stdout.take() {
+        pending.lock().unwrap().open_readers += 1;
+        spawn_log_reader(
+            app_handle.clone(),
+            stdout,
+            server,
+            "stdout",
+            log_file.clone(),
+            pending.clone(),
+        );
+    }
+    if let Some(stderr) = child.stderr.take() {
+        pending.lock().unwrap().open_readers += 1;
+        spawn_log_reader(app_handle.clone(), stderr, server, "stderr", log_file, pending.clone());
+    }
+    spawn_log_flusher(app_handle.clone(), server, pending);
+}
+
+// Emits the pending lines of a server every LOG_BATCH_INTERVAL until both readers hit EOF,
+// so a chatty server costs at most one IPC event per channel per interval
+fn spawn_log_flusher(
+    app_handle: tauri::AppHandle,
+    server: &'static str,
+    pending: Arc<Mutex<PendingLogLines>>,
+) {
+    let event = format!("{}-log", server);
+    std::thread::spawn(move || loop {
+        std::thread::sleep(LOG_BATCH_INTERVAL);
+
+        let (batch, done) = {
+            let mut pending = pending.lock().unwrap();
+            let batch = ProcessLogBatch {
+                lines: std::mem::take(&mut pending.lines),
+                dropped: std::mem::take(&mut pending.dropped),
+            };
+            (batch, pending.open_readers == 0)
+        };
+        // log://line carries every server on one channel, `<server>-log` just this one
+        if !batch.lines.is_empty() || batch.dropped > 0 {
+            let _ = app_handle.emit(
+                "log://line",
+                ProcessLogEvent {
+                    process_id: server.to_string(),
+                    lines: batch.lines.clone(),
+                    dropped: batch.dropped,
+                },
+            );
+            let _ = app_handle.emit(&event, batch);
+        }
+        if done {
+            break;
+        }
+    });
+}
+
+fn spawn_log_reader(
+    app_handle: tauri::AppHandle,
+    reader: impl std::io::Read + Send + 'static,
+    server: &'static str,
+    stream: &'static str,
+    log_file: Arc<Mutex<Option<LogWriter>>>,
+    pending: Arc<Mutex<PendingLogLines>>,
+) {
+    std::thread::spawn(move || {
+        use std::io::BufRead;
+
+        // read_until returns 0 once the child exits and closes its end of the pipe
+        let mut reader = std::io::BufReader::new(reader);
+        let mut buf = Vec::new();
+        while let Ok(n) = reader.read_until(b'\n', &mut buf) {
+            if n == 0 {
+                break;
+            }
+            let line = String::from_utf8_lossy(&buf).trim_end().to_string();
+            log::info!(target: PROCESS_LOG_TARGET, "[{}] [{}] {}", server, stream, line);
+            if let Some(writer) = log_file.lock().unwrap().as_mut() {
+                if let Err(e) = writer.write_line(&format!("[{}] {}", stream, line)) {
+                    log::warn!("Failed to write {} log: {}", server, e);
+                }
+            }
+            if server == "fastapi" {
+                if let Some(port) = parse_uvicorn_port(&line) {
+                    *app_handle.state::<FastAPIBoundPort>().lock().unwrap() = Some(port);
+                }
+            }
+            if stream == "stderr" {
+                let tails = app_handle.state::<StderrTails>();
+                let mut tails = tails.lock().unwrap();
+                let tail = tails.entry(server).or_default();
+                if tail.len() == STDERR_TAIL_LINES {
+                    tail.pop_front();
+                }
+                tail.push_back(line.clone());
+            }
+            {
+                let logs = app_handle.state::<ProcessLogs>();
+                let mut logs = logs.lock().unwrap();
+                let buffer = logs.entry(server).or_default();
+                if buffer.len() == PROCESS_LOG_LINES {
+                    buffer.pop_front();
+                }
+                buffer.push_back(ProcessLogLine {
+                    stream: stream.to_string(),
+                    line: line.clone(),
+                });
+            }
+            {
+                let mut pending = pending.lock().unwrap();
+                if pending.lines.len() < MAX_PENDING_LOG_LINES {
+                    pending.lines.push(ProcessLogLine {
+                        stream: stream.to_string(),
+                        line,
+                    });
+                } else {
+                    pending.dropped += 1;
+                }
+            }
+            buf.clear();
+        }
+        pending.lock().unwrap().open_readers -= 1;
+    });
+}
+
+// Log target for captured server output, so it can be filtered apart from the app's own logs
+const PROCESS_LOG_TARGET: &str = "wirecraft::process";
+
+// Helper function to check whether WIRECRAFT_LOG_FORMAT asks for JSON log lines
+fn json_log_format_enabled() -> bool {
+    std::env::var("WIRECRAFT_LOG_FORMAT").is_ok_and(|value| value.eq_ignore_ascii_case("json"))
+}
+
+// Helper function to format a log record as a single JSON object per line
+fn format_json_log(
+    out: tauri_plugin_log::fern::FormatCallback,
+    message: &std::fmt::Arguments,
+    record: &log::Record,
+) {
+    let timestamp = time::OffsetDateTime::now_utc()
+        .format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_default();
+    let line = serde_json::json!({
+        "timestamp": timestamp,
+        "level": record.level().as_str(),
+        "target": record.target(),
+        "message": message.to_string(),
+    });
+    out.finish(format_args!("{}", line))
+}
+
+// Helper function to get the captured stderr tail of a server, if it wrote any
+fn stderr_tail(app_handle: &tauri::AppHandle, server: &str) -> Option<String> {
+    let tails = app_handle.state::<StderrTails>();
+    let tails = tails.lock().unwrap();
+    let tail = tails.get(server).filter(|tail| !tail.is_empty())?;
+    Some(tail.iter().cloned().collect::<Vec<_>>().join("\n"))
+}
+
+// Helper function to get Bun executable path
+fn get_bun_path() -> Result<String, AppError> {
+    // First try to find bun in PATH
+    if let Ok(bun_path) = which::which("bun") {
+        return Ok(bun_path.to_string_lossy().to_string());
+    }
+    
+    // If not in PATH, check common installation locations. install_bun doesn't
+    // update our PATH, so this is also how a bun installed this session is found
+    let home_dir = home_directory().ok();
+    let bun_install = std::env::var("BUN_INSTALL").ok();
+    find_installed_bun(bun_install_candidates(home_dir.as_deref(), bun_install.as_deref()))
+}
+
+// Helper function to pick the first candidate that is an existing file
+fn find_installed_bun(candidates: Vec<std::path::PathBuf>) -> Result<String, AppError> {
+    candidates
+        .into_iter()
+        .find(|candidate| candidate.is_file())
+        .map(|candidate| candidate.to_string_lossy().to_string())
+        .ok_or(AppError::BunNotFound)
+}
+
+// Helper function to list the places Bun's installers put the executable, given the home
+// directory and BUN_INSTALL
+fn bun_install_candidates(
+    home_dir: Option<&std::path::Path>,
+    bun_install: Option<&str>,
+) -> Vec<std::path::PathBuf> {
+    let bun_exe = if cfg!(windows) { "bun.exe" } else { "bun" };
+    let mut candidates = Vec::new();
+
+    // The official installer honours BUN_INSTALL before falling back to ~/.bun
+    if let Some(bun_install) = bun_install.filter(|dir| !dir.is_empty()) {
+        candidates.push(std::path::Path::new(bun_install).join("bin").join(bun_exe));
+    }
+
+    // Bun installs to ~/.bun, which is %USERPROFILE%\.bun on Windows
+    if let Some(home_dir) = home_dir {
+        candidates.push(home_dir.join(".bun").join("bin").join(bun_exe));
+    }
+
+    if cfg!(windows) {
+        // Scoop shims live under %SCOOP% or %USERPROFILE%\scoop
+        if let Ok(scoop_dir) = std::env::var("SCOOP") {
+            candidates.push(std::path::Path::new(&scoop_dir).join("shims").join(bun_exe));
+        }
+        if let Some(home_dir) = home_dir {
+            candidates.push(home_dir.join("scoop").join("shims").join(bun_exe));
+        }
+
+        let chocolatey_dir = std::env::var("ChocolateyInstall")
+            .unwrap_or_else(|_| "C:\\ProgramData\\chocolatey".to_string());
+        candidates.push(std::path::Path::new(&chocolatey_dir).join("bin").join(bun_exe));
+    }
+
+    candidates
+}
+
+// Helper function to get Python executable path
+fn get_python_path(app_handle: &tauri::AppHandle) -> Result<String, AppError> {
+    resolve_python(app_handle).map(|(python_path, _)| python_path)
+}
+
+// Helper function to pick the Python interpreter: the configured python_path if there is
+// one, otherwise whatever find_python discovers
+fn resolve_python(app_handle: &tauri::AppHandle) -> Result<(String, String), AppError> {
+    match server_config(app_handle).python_path {
+        Some(python_path) => configured_python(&python_path),
+        None => find_python(),
+    }
+}
+
+// Helper function to run `<python> --version`. Python 2 prints it to stderr.
+fn python_version_output(python_path: &std::path::Path) -> std::io::Result<String> {
+    let output = Command::new(python_path).arg("--version").output()?;
+    if !output.status.success() {
+        return Err(std::io::Error::other(format!("--version exited with {}", output.status)));
+    }
+    let mut version_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if version_str.is_empty() {
+        version_str = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    }
+    Ok(version_str)
+}
+
+// Helper function to validate an explicitly configured interpreter. Unlike discovery there
+// is no fallback, so every problem is reported against the configured path.
+fn configured_python(python_path: &str) -> Result<(String, String), AppError> {
+    let invalid = |reason: String| AppError::InvalidArgument {
+        reason: format!("Configured python_path {} is not usable: {}", python_path, reason),
+    };
+
+    let version_str = python_version_output(std::path::Path::new(python_path))
+        .map_err(|e| invalid(e.to_string()))?;
+    let version = parse_python_version(&version_str)
+        .ok_or_else(|| invalid(format!("unrecognized version output {:?}", version_str)))?;
+    let version_str = version_str.trim_start_matches("Python ").to_string();
+    if version < MIN_PYTHON {
+        return Err(AppError::PythonTooOld {
+            found: version_str,
+            required: format!("{}.{}", MIN_PYTHON.0, MIN_PYTHON.1),
+        });
+    }
+
+    log::info!("Using configured Python: {} ({})", python_path, version_str);
+    Ok((python_path.to_string(), version_str))
+}
+
+// Helper function to parse `Python 3.11.4` into its (major, minor) version
+fn parse_python_version(output: &str) -> Option<(u32, u32)> {
+    let version = output.trim().strip_prefix("Python ")?;
+    let mut parts = version.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+// Helper function to find a Python interpreter new enough for the FastAPI app,
+// returning its path and reported version
+fn find_python() -> Result<(String, String), AppError> {
+    let mut newest_rejected: Option<((u32, u32), String)> = None;
+
+    // Try python3.11 first (preferred), then python3, then python
+    for python_cmd in &["python3.11", "python3", "python"] {
+        let Ok(python_path) = which::which(python_cmd) else {
+            continue;
+        };
+        let Ok(version_str) = python_version_output(&python_path) else {
+            continue;
+        };
+        let Some(version) = parse_python_version(&version_str) else {
+            log::warn!("Could not parse Python version from {:?}", version_str);
+            continue;
+        };
+
+        if version < MIN_PYTHON {
+            log::warn!(
+                "Skipping {} ({}): Python {}.{}+ is required",
+                python_path.display(),
+                version_str,
+                MIN_PYTHON.0,
+                MIN_PYTHON.1
+            );
+            if !matches!(&newest_rejected, Some((newest, _)) if *newest >= version) {
+                newest_rejected = Some((version, version_str));
+            }
+            continue;
+        }
+
+        log::info!("Found Python: {} ({})", python_path.display(), version_str);
+        let version_str = version_str.trim_start_matches("Python ").to_string();
+        return Ok((python_path.to_string_lossy().to_string(), version_str));
+    }
+
+    match newest_rejected {
+        Some((_, found)) => Err(AppError::PythonTooOld {
+            found: found.trim_start_matches("Python ").to_string(),
+            required: format!("{}.{}", MIN_PYTHON.0, MIN_PYTHON.1),
+        }),
+        None => Err(AppError::PythonNotFound),
+    }
+}
+
+// Helper function to parse `--version` output as SemVer, padding short versions like "3.12"
+fn parse_semver(raw: &str) -> Option<semver::Version> {
+    let version = raw.trim().trim_start_matches("Python ").trim_start_matches('v');
+    if let Ok(version) = semver::Version::parse(version) {
+        return Some(version);
+    }
+    // Pre-releases such as "3.13.0rc1" aren't SemVer; keep just the numeric core
+    let core: Vec<u64> = version
+        .split('.')
+        .map_while(|part| {
+            let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
+            digits.parse().ok()
+        })
+        .take(3)
+        .collect();
+    match core.as_slice() {
+        [major, minor, patch] => Some(semver::Version::new(*major, *minor, *patch)),
+        [major, minor] => Some(semver::Version::new(*major, *minor, 0)),
+        _ => None,
+    }
+}
+
+fn runtime_version(raw: Option<String>, minimum: semver::Version) -> RuntimeVersion {
+    let version = raw.as_deref().and_then(parse_semver);
+    let compatible = version.as_ref().is_some_and(|version| *version >= minimum);
+    RuntimeVersion {
+        raw,
+        version,
+        compatible,
+    }
+}
+
+// Bun and Python versions as SemVer, checked against MIN_BUN and MIN_PYTHON
+#[tauri::command]
+async fn get_runtime_versions(app_handle: tauri::AppHandle) -> Result<RuntimeVersions, AppError> {
+    let bun_raw = check_bun_installation().await?.version;
+    let python_raw = match run_blocking(move || resolve_python(&app_handle)).await? {
+        Ok((_, version)) => Some(version),
+        Err(AppError::PythonTooOld { found, .. }) => Some(found),
+        Err(_) => None,
+    };
+
+    let bun = runtime_version(bun_raw, min_bun());
+    let python = runtime_version(
+        python_raw,
+        semver::Version::new(MIN_PYTHON.0.into(), MIN_PYTHON.1.into(), 0),
+    );
+    Ok(RuntimeVersions {
+        compatible: bun.compatible && python.compatible,
+        bun,
+        python,
+    })
+}
+
+// Helper function to detect a version-manager shim and resolve the binary behind it
+fn detect_toolchain_shim(tool: &str, path: &str) -> Option<ToolchainDiagnostic> {
+    let shim_path = std::path::Path::new(path);
+    let shim_dir = shim_path.parent()?;
+    let (manager, _) = SHIM_MANAGERS
+        .iter()
+        .find(|(_, dir)| shim_dir.ends_with(dir))?;
+
+    // Every supported manager understands `<manager> which <binary>`
+    let binary_name = shim_path.file_name()?.to_string_lossy().to_string();
+    let resolved_path = which::which(manager)
+        .ok()
+        .and_then(|manager_path| {
+            Command::new(manager_path)
+                .args(["which", &binary_name])
+                .output()
+                .ok()
+        })
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|resolved| std::path::Path::new(resolved).exists());
+
+    log::warn!(
+        "{} at {} is a {} shim; consider pinning the real binary{}",
+        tool,
+        path,
+        manager,
+        resolved_path
+            .as_ref()
+            .map(|p| format!(" ({})", p))
+            .unwrap_or_default()
+    );
+
+    Some(ToolchainDiagnostic::ToolchainIsShim {
+        tool: tool.to_string(),
+        manager: manager.to_string(),
+        path: path.to_string(),
+        resolved_path,
+    })
+}
+
+// Helper function behind check_bun_installation; runs `bun --version`, so keep it off the
+// async runtime
+fn bun_status() -> BunStatus {
+    match get_bun_path() {
+        Ok(bun_path) => {
+            // Try to get version
+            if let Ok(output) = Command::new(&bun_path).arg("--version").output() {
+                if output.status.success() {
+                    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                    return BunStatus {
+                        installed: true,
+                        compatible: parse_semver(&version).is_some_and(|v| v >= min_bun()),
+                        version: Some(version),
+                        path: Some(bun_path),
+                    };
+                }
+            }
+            
+            // Bun found but can't get version
+            BunStatus {
+                installed: true,
+                version: None,
+                path: Some(bun_path),
+                compatible: false,
+            }
+        }
+        Err(_) => {
+            // Bun not found
+            BunStatus {
+                installed: false,
+                version: None,
+                path: None,
+                compatible: false,
+            }
+        }
+    }
+}
+
+#[tauri::command]
+async fn check_bun_installation() -> Result<BunStatus, AppError> {
+    run_blocking(bun_status).await
+}
+
+#[tauri::command]
+async fn check_python_installation(app_handle: tauri::AppHandle) -> Result<PythonStatus, AppError> {
+    match run_blocking(move || resolve_python(&app_handle)).await? {
+        Ok((python_path, version)) => Ok(PythonStatus {
+            installed: true,
+            version: Some(version),
+            path: Some(python_path),
+        }),
+        Err(AppError::PythonNotFound) => Ok(PythonStatus {
+            installed: false,
+            version: None,
+            path: None,
+        }),
+        Err(e) => Err(e),
+    }
+}
+
+#[tauri::command]
+async fn check_toolchain_shims(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<ToolchainDiagnostic>, AppError> {
+    run_blocking(move || {
+        [("bun", get_bun_path()), ("python", get_python_path(&app_handle))]
+            .into_iter()
+            .filter_map(|(tool, path)| path.ok().and_then(|p| detect_toolchain_shim(tool, &p)))
+            .collect()
+    })
+    .await
+}
+
+#[tauri::command]
+async fn install_bun(
+    app_handle: tauri::AppHandle,
+    timeout_secs: Option<u64>,
+    offline: Option<bool>,
+    verify_checksum: Option<bool>,
+) -> Result<String, AppError> {
+    let cancel = begin_operation(&app_handle);
+
+    if offline.unwrap_or_else(|| server_config(&app_handle).offline()) {
+        // Air-gapped installs must pre-stage Bun; never try to download it
+        return match get_bun_path() {
+            Ok(bun_path) => Ok(format!("Bun is already installed at {}", bun_path)),
+            Err(_) => Err(AppError::Offline {
+                action: "Installing Bun".to_string(),
+            }),
+        };
+    }
+
+    let timeout = std::time::Duration::from_secs(timeout_secs.unwrap_or(BUN_INSTALL_TIMEOUT_SECS));
+
+    // Verify whenever a checksum is known, unless the caller opts out
+    let pinned = bun_installer_sha256(server_config(&app_handle).bun_installer_sha256);
+    let expected = match (verify_checksum.unwrap_or(pinned.is_some()), pinned) {
+        (true, Some(expected)) => Some(expected),
+        (true, None) => {
+            return Err(AppError::InvalidArgument {
+                reason: format!(
+                    "No pinned checksum for the Bun {} installer; set bun_installer_sha256 in \
+                     the config",
+                    BUN_VERSION
+                ),
+            })
+        }
+        (false, _) => None,
+    };
+    run_bun_installer(expected.as_deref(), timeout, &cancel).await?;
+
+    let status = check_bun_installation().await?;
+    match status.version {
+        Some(version) => Ok(format!("Bun {} installed successfully", version)),
+        None => Ok("Bun installed successfully".to_string()),
+    }
+}
+
+// Bun release install_bun installs. The script comes from the same release tag, so unlike
+// bun.sh/install its contents (and checksum) can't change underneath a pinned hash.
+const BUN_VERSION: &str = "1.1.38";
+
+// The official install script at BUN_VERSION; PowerShell on Windows
+const BUN_INSTALLER_URL: &str = if cfg!(target_os = "windows") {
+    "https://raw.githubusercontent.com/oven-sh/bun/bun-v1.1.38/src/cli/install.ps1"
+} else {
+    "https://raw.githubusercontent.com/oven-sh/bun/bun-v1.1.38/src/cli/install.sh"
+};
+
+// SHA-256 of BUN_INSTALLER_URL per platform ("unix" or "windows"), updated together with
+// BUN_VERSION. A platform without an entry relies on the build-time or config override.
+const PINNED_BUN_INSTALLER_SHA256: &[(&str, &str)] = &[];
+
+// Helper function to pick the checksum the Bun installer must match: the config wins, then
+// WIRECRAFT_BUN_INSTALLER_SHA256 at build time, then the one shipped for this platform
+fn bun_installer_sha256(configured: Option<String>) -> Option<String> {
+    let platform = if cfg!(target_os = "windows") { "windows" } else { "unix" };
+    configured
+        .filter(|sha| !sha.trim().is_empty())
+        .or_else(|| option_env!("WIRECRAFT_BUN_INSTALLER_SHA256").map(str::to_string))
+        .or_else(|| {
+            PINNED_BUN_INSTALLER_SHA256
+                .iter()
+                .find(|(os, _)| *os == platform)
+                .map(|(_, sha)| sha.to_string())
+        })
+}
+
+// Helper function to download the Bun installer to a temp file, check it against the
+// expected SHA-256 if there is one, and only then run it. The temp file is removed either way.
+// Every program is invoked directly, never through `sh -c`.
+async fn run_bun_installer(
+    expected: Option<&str>,
+    timeout: std::time::Duration,
+    cancel: &CancellationToken,
+) -> Result<(), AppError> {
+    let extension = if cfg!(target_os = "windows") { "ps1" } else { "sh" };
+    let installer = std::env::temp_dir().join(format!(
+        "wirecraft-bun-install-{}.{}",
+        std::process::id(),
+        extension
+    ));
+
+    let result = async {
+        let download = installer_download_command(&installer)?;
+        run_installer_step(download, "Bun installer download", timeout, cancel).await?;
+
+        if let Some(expected) = expected {
+            let actual = manifest::hash_file(&installer)
+                .map_err(|e| AppError::io("Failed to hash the Bun installer", e))?;
+            if !actual.eq_ignore_ascii_case(expected.trim()) {
+                return Err(AppError::ChecksumMismatch {
+                    file: BUN_INSTALLER_URL.to_string(),
+                    expected: expected.trim().to_lowercase(),
+                    actual,
+                });
+            }
+            log::info!("Bun installer checksum verified ({})", actual);
+        }
+
+        let run = if cfg!(target_os = "windows") {
+            let mut command = tokio::process::Command::new("powershell");
+            command.args(["-ExecutionPolicy", "Bypass", "-File"]).arg(&installer);
+            command.args(["-Version", BUN_VERSION]);
+            command
+        } else {
+            // The installer is a bash script
+            let bash = which::which("bash")
+                .map_err(|_| AppError::not_found("bash (the Bun installer is a bash script)"))?;
+            let mut command = tokio::process::Command::new(bash);
+            command.arg(&installer).arg(format!("bun-v{}", BUN_VERSION));
+            command
+        };
+        run_installer_step(run, "Bun installer", timeout, cancel).await
+    }
+    .await;
+
+    let _ = std::fs::remove_file(&installer);
+    result
+}
+
+// Helper function to build the command that downloads the Bun installer to `dest`. Unix
+// uses curl, or wget on minimal images without it.
+fn installer_download_command(dest: &std::path::Path) -> Result<tokio::process::Command, AppError> {
+    if cfg!(target_os = "windows") {
+        // The path goes through the environment so it never needs quoting in the script
+        let mut command = tokio::process::Command::new("powershell");
+        command
+            .args([
+                "-Command",
+                &format!(
+                    "Invoke-WebRequest -UseBasicParsing -Uri {} -OutFile $env:WIRECRAFT_BUN_INSTALLER",
+                    BUN_INSTALLER_URL
+                ),
+            ])
+            .env("WIRECRAFT_BUN_INSTALLER", dest);
+        return Ok(command);
+    }
+
+    if let Ok(curl) = which::which("curl") {
+        let mut command = tokio::process::Command::new(curl);
+        command.args(["-fsSL", "-o"]).arg(dest).arg(BUN_INSTALLER_URL);
+        Ok(command)
+    } else if let Ok(wget) = which::which("wget") {
+        let mut command = tokio::process::Command::new(wget);
+        command.args(["-q", "-O"]).arg(dest).arg(BUN_INSTALLER_URL);
+        Ok(command)
+    } else {
+        Err(AppError::not_found(
+            "curl or wget to download Bun (install either with your system package manager)",
+        ))
+    }
+}
+
+// Helper function to run one step of the Bun install, killing it on timeout or cancellation
+async fn run_installer_step(
+    mut command: tokio::process::Command,
+    name: &str,
+    timeout: std::time::Duration,
+    cancel: &CancellationToken,
+) -> Result<(), AppError> {
+    // Spawned rather than awaited with output() so a stalled download can be killed
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| AppError::spawn(name, e))?;
+    let (stdout, stdout_reader) = collect_output(child.stdout.take());
+    let (stderr, stderr_reader) = collect_output(child.stderr.take());
+
+    let status = tokio::select! {
+        result = tokio::time::timeout(timeout, child.wait()) => match result {
+            Ok(status) => status.map_err(|e| AppError::io(format!("Failed to wait for {}", name), e))?,
+            Err(_) => {
+                let _ = child.kill().await;
+                let stderr = stderr.lock().unwrap().trim().to_string();
+                return Err(AppError::Timeout {
+                    operation: name.to_string(),
+                    seconds: timeout.as_secs(),
+                    stderr,
+                });
+            }
+        },
+        _ = cancel.cancelled() => {
+            let _ = child.kill().await;
+            return Err(AppError::OperationCancelled);
+        }
+    };
+    let _ = tokio::join!(stdout_reader, stderr_reader);
+
+    if status.success() {
+        Ok(())
+    } else {
+        let stdout = stdout.lock().unwrap().trim().to_string();
+        let stderr = stderr.lock().unwrap().trim().to_string();
+        Err(AppError::CommandFailed {
+            command: name.to_string(),
+            stderr: format!("{}\nstdout: {}", stderr, stdout),
+        })
+    }
+}
+
+// Helper function to run blocking filesystem or process work on the blocking pool, so a
+// long install doesn't stall the async runtime and other commands keep responding
+async fn run_blocking<T: Send + 'static>(
+    work: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, AppError> {
+    tauri::async_runtime::spawn_blocking(work)
+        .await
+        .map_err(|e| AppError::io("Background task failed", e))
+}
+
+// Helper function to stop the MCP processes without blocking the async runtime
+async fn stop_mcp_processes_async(app_handle: &tauri::AppHandle) -> Result<Vec<u32>, AppError> {
+    let app_handle = app_handle.clone();
+    run_blocking(move || stop_mcp_processes(&app_handle)).await
+}
+
+// Helper function to take the install lock, either failing with Busy straight away or,
+// with wait_for_install_lock set, queueing behind the running operation
+async fn acquire_install_lock(
+    app_handle: &tauri::AppHandle,
+) -> Result<tokio::sync::OwnedMutexGuard<()>, AppError> {
+    let lock = app_handle.state::<InstallLock>().inner().clone();
+    if server_config(app_handle).wait_for_install_lock {
+        return Ok(lock.lock_owned().await);
+    }
+    lock.try_lock_owned().map_err(|_| AppError::Busy {
+        operation: "An MCP server install, update or uninstall".to_string(),
+    })
+}
+
+// Helper function to start a cancellable operation, replacing any previous token
+fn begin_operation(app_handle: &tauri::AppHandle) -> CancellationToken {
+    let token = CancellationToken::new();
+    *app_handle.state::<CurrentOperation>().lock().unwrap() = token.clone();
+    token
+}
+
+// Helper function to stop between phases once cancellation has been requested
+fn check_cancelled(token: &CancellationToken) -> Result<(), AppError> {
+    if token.is_cancelled() {
+        Err(AppError::OperationCancelled)
+    } else {
+        Ok(())
+    }
+}
+
+// Helper function to run a command to completion, killing it if the operation is cancelled
+async fn run_cancellable(
+    mut command: tokio::process::Command,
+    token: &CancellationToken,
+) -> Result<std::io::Result<std::process::Output>, AppError> {
+    let child = match command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => return Ok(Err(e)),
+    };
+
+    // Dropping the wait future drops the child, and kill_on_drop kills it
+    tokio::select! {
+        output = child.wait_with_output() => Ok(output),
+        _ = token.cancelled() => Err(AppError::OperationCancelled),
+    }
+}
+
+#[tauri::command]
+async fn cancel_current_operation(app_handle: tauri::AppHandle) -> Result<(), AppError> {
+    app_handle.state::<CurrentOperation>().lock().unwrap().cancel();
+    Ok(())
+}
+
+// Helper function to accumulate a child's output in the background so whatever
+// arrived is still available if the child has to be killed
+fn collect_output(
+    reader: Option<impl tokio::io::AsyncRead + Unpin + Send + 'static>,
+) -> (Arc<Mutex<String>>, tauri::async_runtime::JoinHandle<()>) {
+    let output = Arc::new(Mutex::new(String::new()));
+    let sink = output.clone();
+    let handle = tauri::async_runtime::spawn(async move {
+        use tokio::io::AsyncBufReadExt;
+
+        let Some(reader) = reader else {
+            return;
+        };
+        let mut lines = tokio::io::BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let mut sink = sink.lock().unwrap();
+            sink.push_str(&line);
+            sink.push('\n');
+        }
+    });
+    (output, handle)
+}
+
+// Helper function to get the user's home directory on every platform; Windows
+// usually has no HOME, so this goes through the OS lookup rather than the env var
+fn home_directory() -> Result<std::path::PathBuf, AppError> {
+    dirs::home_dir().ok_or(AppError::HomeDirNotFound)
+}
+
+// Helper function to get the app's data directory: $WIRECRAFT_HOME, else ~/.wirecraft
+pub(crate) fn wirecraft_dir() -> Result<std::path::PathBuf, AppError> {
+    if let Ok(wirecraft_home) = std::env::var("WIRECRAFT_HOME") {
+        if !wirecraft_home.is_empty() {
+            return Ok(std::path::PathBuf::from(wirecraft_home));
+        }
+    }
+
+    Ok(home_directory()?.join(".wirecraft"))
+}
+
+// Helper function to resolve where the MCP server is installed, honouring an explicit
+// override, then the configured install_dir
+fn mcp_server_dir(
+    app_handle: &tauri::AppHandle,
+    install_dir: Option<&str>,
+) -> Result<std::path::PathBuf, AppError> {
+    let configured = server_config(app_handle).install_dir;
+    match install_dir.or(configured.as_deref()) {
+        Some(dir) if !dir.is_empty() => Ok(std::path::PathBuf::from(dir)),
+        _ => Ok(wirecraft_dir()?.join("mcp-server")),
+    }
+}
+
+// What install_mcp_server would do, as reported by a dry run
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InstallPlan {
+    pub source_dir: String,
+    pub destination: String,
+    pub file_count: u64,
+    pub bundle_version: Option<String>,
+    pub installed_version: Option<String>,
+    // Bundle subdirectory being installed, and the one currently installed
+    pub variant: Option<String>,
+    pub installed_variant: Option<String>,
+    // False when the same version is already installed and intact
+    pub copy_needed: bool,
+    pub dependencies_needed: bool,
+    // Switching variants removes the previous variant's files before copying
+    pub clean_reinstall: bool,
+}
+
+// Result of install_mcp_server: the install summary, or the plan when dry_run is set
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum InstallOutcome {
+    Installed(String),
+    Planned(InstallPlan),
+}
+
+// Result of get_installed_mcp_version
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InstalledMcpVersion {
+    pub version: Option<String>,
+    pub variant: Option<String>,
+}
+
+// How reload_mcp_server reloaded the MCP server
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum McpReloadKind {
+    // SIGHUP; the process reloaded itself in place
+    Signal,
+    // Only server.ts was respawned; the socket server kept its connections
+    ServerRestart,
+    // Both processes were restarted
+    FullRestart,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct McpReloadResult {
+    pub kind: McpReloadKind,
+    pub pid: Option<u32>,
+}
+
+// The paths the last install_mcp_server and start_fastapi_server actually used, as
+// reported by last_resolution. None until the corresponding command has run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PathResolution {
+    pub resource_dir: Option<String>,
+    pub current_dir: Option<String>,
+    pub mcp_source_dir: Option<String>,
+    pub mcp_install_dir: Option<String>,
+    pub bun_path: Option<String>,
+    pub fastapi_dir: Option<String>,
+    pub python_path: Option<String>,
+    pub venv_python: Option<String>,
+}
+
+// Result of check_mcp_ready: what a probe instance of the MCP server answered
+#[derive(Debug, Serialize, Deserialize)]
+pub struct McpReadiness {
+    pub server_name: Option<String>,
+    pub server_version: Option<String>,
+    pub protocol_version: Option<String>,
+    pub tool_count: usize,
+    pub elapsed_ms: u64,
+}
+
+// Result of get_mcp_package_info; scripts holds the script names, sorted
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PackageInfo {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub description: Option<String>,
+    pub scripts: Vec<String>,
+}
+
+// Result of check_mcp_update; current is None when the MCP server isn't installed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateInfo {
+    pub current: Option<String>,
+    pub available: Option<String>,
+    pub update_available: bool,
+}
+
+// Result of update_mcp_server
+#[derive(Debug, Serialize, Deserialize)]
+pub struct McpUpdateSummary {
+    pub files_updated: usize,
+    pub dependencies_installed: bool,
+    pub version: Option<String>,
+    pub restarted: bool,
+}
+
+// Result of rebuild_fastapi_venv; dependencies_installed is false if any pip install failed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VenvRebuildSummary {
+    pub venv_dir: String,
+    pub python: String,
+    pub dependencies_installed: bool,
+    pub restarted: bool,
+}
+
+// One file of list_installed_files; modified is unix seconds
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileEntry {
+    pub path: String,
+    pub size: u64,
+    pub modified: Option<u64>,
+}
+
+// What the app does on launch; see ServerConfig::auto_start
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AutoStartSettings {
+    pub auto_start: bool,
+    pub auto_install: bool,
+}
+
+// Result of reinstall_mcp_dependencies
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DependencyReinstall {
+    pub force: bool,
+    pub attempts: u32,
+}
+
+// Bundle resolution
+//
+// Candidate lists are built from the resource and working directories passed in,
+// so the search order can be exercised without a running app.
+
+// Where a bundled directory lives: the resource dir (production), the _up_/_up_
+// layout Tauri uses for `../../` resources, then the project root (development)
+fn bundle_candidates(
+    resource_dir: Option<&std::path::Path>,
+    current_dir: Option<&std::path::Path>,
+    bundle_name: &str,
+) -> Vec<std::path::PathBuf> {
+    let mut candidates = Vec::new();
+    if let Some(resource_dir) = resource_dir {
+        candidates.push(resource_dir.join(bundle_name));
+        candidates.push(resource_dir.join("_up_").join("_up_").join(bundle_name));
+    }
+    // From src-tauri up to tauri-mcp-client, then up to the project root
+    if let Some(project_root) = current_dir.and_then(|dir| dir.parent()).and_then(|dir| dir.parent()) {
+        candidates.push(project_root.join(bundle_name));
+    }
+    candidates
+}
+
+// Where the FastAPI app lives: next to src-tauri in development, then beside the resource dir
+fn fastapi_candidates(
+    resource_dir: Option<&std::path::Path>,
+    current_dir: Option<&std::path::Path>,
+) -> Vec<std::path::PathBuf> {
+    [current_dir, resource_dir]
+        .into_iter()
+        .flatten()
+        .filter_map(|dir| dir.parent())
+        .map(|dir| dir.join("resource/mcp-client-python/api"))
+        .collect()
+}
+
+fn first_existing(candidates: Vec<std::path::PathBuf>) -> Option<std::path::PathBuf> {
+    candidates.into_iter().find(|candidate| {
+        log::info!("Checking candidate: {:?}", candidate);
+        candidate.exists()
+    })
+}
+
+fn resolve_bundle_dir(app_handle: &tauri::AppHandle, bundle_name: &str) -> Option<std::path::PathBuf> {
+    let resource_dir = app_handle.path().resource_dir().ok();
+    let current_dir = std::env::current_dir().ok();
+    first_existing(bundle_candidates(
+        resource_dir.as_deref(),
+        current_dir.as_deref(),
+        bundle_name,
+    ))
+}
+
+fn resolve_fastapi_dir(app_handle: &tauri::AppHandle) -> Option<std::path::PathBuf> {
+    let resource_dir = app_handle.path().resource_dir().ok();
+    let current_dir = std::env::current_dir().ok();
+    first_existing(fastapi_candidates(resource_dir.as_deref(), current_dir.as_deref()))
+}
+
+// Helper function to resolve and validate everything an install needs, without writing anything
+fn plan_install(
+    app_handle: &tauri::AppHandle,
+    install_dir: Option<&str>,
+    force: bool,
+    variant: Option<&str>,
+) -> Result<InstallPlan, AppError> {
+    let mcp_server_dir = mcp_server_dir(app_handle, install_dir)?;
+    let bundle_dir = resolve_bundle_dir(app_handle, "mcp-server-bundle").ok_or_else(|| {
+        AppError::not_found("MCP server bundle in resources or development path")
+    })?;
+    log::info!("Found MCP server bundle at: {:?}", bundle_dir);
+
+    // A variant is a build in its own subdirectory, e.g. mcp-server-bundle/figma
+    let variant = variant.filter(|variant| !variant.is_empty());
+    let source_dir = match variant {
+        Some(variant) => {
+            let is_plain_name = std::path::Path::new(variant)
+                .components()
+                .all(|component| matches!(component, std::path::Component::Normal(_)));
+            if !is_plain_name || variant.contains(['/', '\\']) {
+                return Err(AppError::InvalidArgument {
+                    reason: format!("Invalid MCP server variant '{}'", variant),
+                });
+            }
+            let source_dir = bundle_dir.join(variant);
+            if !source_dir.is_dir() {
+                return Err(AppError::not_found(format!("MCP server variant '{}'", variant)));
+            }
+            source_dir
+        }
+        None => bundle_dir,
+    };
+
+    // Catch a wrong directory now rather than when start_mcp_server fails later
+    if let Some(missing) = REQUIRED_BUNDLE_FILES
+        .iter()
+        .find(|file| !source_dir.join(file).is_file())
+    {
+        return Err(AppError::InvalidBundle {
+            path: source_dir.to_string_lossy().to_string(),
+            missing: missing.to_string(),
+        });
+    }
+
+    check_bundle_platform(&source_dir)?;
+
+    let file_count = count_files(&source_dir, &is_bundle_file)
+        .map_err(|e| AppError::io("Failed to read MCP server bundle", e))?;
+    let bundle_version = read_package_version(&source_dir);
+    let installed_version = read_installed_version(&mcp_server_dir);
+    let installed_variant = read_installed_variant(&mcp_server_dir);
+    let variant = variant.map(|variant| variant.to_string());
+    let switching_variant = installed_version.is_some() && installed_variant != variant;
+
+    // Same version already installed and intact: nothing to copy or install
+    let up_to_date = !force
+        && !switching_variant
+        && bundle_version.is_some()
+        && installed_version == bundle_version
+        && mcp_server_dir.join("node_modules").is_dir()
+        && manifest::verify_manifest(&mcp_server_dir).is_ok_and(|result| result.is_valid());
+
+    Ok(InstallPlan {
+        source_dir: source_dir.to_string_lossy().to_string(),
+        destination: mcp_server_dir.to_string_lossy().to_string(),
+        file_count,
+        bundle_version,
+        installed_version,
+        variant,
+        installed_variant,
+        copy_needed: !up_to_date,
+        dependencies_needed: !up_to_date,
+        clean_reinstall: switching_variant,
+    })
+}
+
+// Helper function to tell permission problems (including a read-only filesystem) apart
+// from other IO failures
+fn is_permission_error(e: &std::io::Error) -> bool {
+    #[cfg(unix)]
+    if e.raw_os_error() == Some(libc::EROFS) {
+        return true;
+    }
+    e.kind() == std::io::ErrorKind::PermissionDenied
+}
+
+// Helper function to create the install directory. When it isn't writable (e.g. a read-only
+// $HOME) and install_dir_fallback is set, install under the OS data or temp dir instead and
+// remember that location as the configured install_dir.
+fn create_install_dir(
+    app_handle: &tauri::AppHandle,
+    mcp_server_dir: std::path::PathBuf,
+) -> Result<std::path::PathBuf, AppError> {
+    let e = match std::fs::create_dir_all(&mcp_server_dir) {
+        Ok(()) => return Ok(mcp_server_dir),
+        Err(e) if is_permission_error(&e) => e,
+        Err(e) => return Err(AppError::io(format!("Failed to create {:?}", mcp_server_dir), e)),
+    };
+
+    if !server_config(app_handle).install_dir_fallback {
+        return Err(AppError::PermissionDenied {
+            path: mcp_server_dir.to_string_lossy().to_string(),
+            reason: e.to_string(),
+        });
+    }
+
+    let fallback = dirs::data_local_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("wirecraft")
+        .join("mcp-server");
+    std::fs::create_dir_all(&fallback)
+        .map_err(|e| AppError::io(format!("Failed to create fallback {:?}", fallback), e))?;
+    log::warn!(
+        "{:?} is not writable ({}), installing to {:?} instead",
+        mcp_server_dir,
+        e,
+        fallback
+    );
+
+    // Later commands (start, status, update) resolve the install through the config
+    let config_state = app_handle.state::<ServerConfigState>();
+    config_state.lock().unwrap().install_dir = Some(fallback.to_string_lossy().to_string());
+    Ok(fallback)
+}
+
+// Helper function to remove what a previous install put in place (its manifest-listed
+// files, node_modules and metadata) while leaving any user files alone
+fn clean_install(mcp_server_dir: &std::path::Path) -> Result<(), AppError> {
+    let installed = manifest::read_manifest(mcp_server_dir).unwrap_or_default();
+    let files = installed
+        .keys()
+        .map(|relative| relative.as_str())
+        .chain([manifest::MANIFEST_FILE, VERSION_FILE]);
+    for relative in files {
+        match std::fs::remove_file(mcp_server_dir.join(relative)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(AppError::io(format!("Failed to remove {}", relative), e));
+            }
+            _ => {}
+        }
+    }
+    let node_modules = mcp_server_dir.join("node_modules");
+    if node_modules.exists() {
+        std::fs::remove_dir_all(&node_modules)
+            .map_err(|e| AppError::io(format!("Failed to remove {:?}", node_modules), e))?;
+    }
+    Ok(())
+}
+
+// Alias for install_mcp_server with dry_run set, kept for existing callers
+#[tauri::command]
+async fn plan_mcp_server_install(
+    app_handle: tauri::AppHandle,
+    install_dir: Option<String>,
+    force: Option<bool>,
+    variant: Option<String>,
+) -> Result<InstallPlan, AppError> {
+    match install_mcp_server(app_handle, install_dir, force, variant, Some(true)).await? {
+        InstallOutcome::Planned(plan) => Ok(plan),
+        InstallOutcome::Installed(_) => unreachable!("a dry run never installs"),
+    }
+}
+
+// Install the bundled MCP server. With dry_run the bundle and destination are resolved and
+// the plan returned, but nothing is written and no install lock is taken.
+#[tauri::command]
+async fn install_mcp_server(
+    app_handle: tauri::AppHandle,
+    install_dir: Option<String>,
+    force: Option<bool>,
+    variant: Option<String>,
+    dry_run: Option<bool>,
+) -> Result<InstallOutcome, AppError> {
+    if dry_run.unwrap_or(false) {
+        let force = force.unwrap_or(false);
+        return run_blocking(move || {
+            plan_install(&app_handle, install_dir.as_deref(), force, variant.as_deref())
+        })
+        .await?
+        .map(InstallOutcome::Planned);
+    }
+    install_mcp_server_files(app_handle, install_dir, force, variant)
+        .await
+        .map(InstallOutcome::Installed)
+}
+
+// Helper function doing the actual install for install_mcp_server
+async fn install_mcp_server_files(
+    app_handle: tauri::AppHandle,
+    install_dir: Option<String>,
+    force: Option<bool>,
+    variant: Option<String>,
+) -> Result<String, AppError> {
+    let _install_lock = acquire_install_lock(&app_handle).await?;
+    let cancel = begin_operation(&app_handle);
+    let plan = {
+        let app_handle = app_handle.clone();
+        let force = force.unwrap_or(false);
+        run_blocking(move || {
+            plan_install(&app_handle, install_dir.as_deref(), force, variant.as_deref())
+        })
+        .await??
+    };
+    let source_dir = std::path::PathBuf::from(&plan.source_dir);
+    let mcp_server_dir = std::path::PathBuf::from(&plan.destination);
+    let bundle_version = plan.bundle_version;
+    let bun_path = get_bun_path().ok();
+    record_resolution(&app_handle, |resolution| {
+        resolution.mcp_source_dir = Some(plan.source_dir.clone());
+        resolution.mcp_install_dir = Some(plan.destination.clone());
+        resolution.bun_path = bun_path;
+    });
+
+    if !plan.copy_needed {
+        emit_install_progress(&app_handle, InstallPhase::Done, None);
+        return Ok(format!(
+            "MCP Server {} is already installed at {}",
+            bundle_version.unwrap_or_default(),
+            mcp_server_dir.display()
+        ));
+    }
+
+    // Copy MCP server files to the install directory
+    check_cancelled(&cancel)?;
+    let total_files = plan.file_count;
+    let symlinks = server_config(&app_handle).bundle_symlinks;
+    let mcp_server_dir = {
+        let app_handle = app_handle.clone();
+        let source_dir = source_dir.clone();
+        let clean_reinstall = plan.clean_reinstall;
+        run_blocking(move || -> Result<std::path::PathBuf, AppError> {
+            // Create the install directory if it doesn't exist
+            let mcp_server_dir = create_install_dir(&app_handle, mcp_server_dir)?;
+
+            // Fail before copying rather than leaving a half-written install on a full disk
+            check_disk_space(&source_dir, &mcp_server_dir)?;
+
+            // Files from another variant must not linger alongside the new one
+            if clean_reinstall {
+                clean_install(&mcp_server_dir)?;
+            }
+
+            let mut files_copied = 0;
+            emit_install_progress(&app_handle, InstallPhase::CopyingFiles, Some((0, total_files)));
+            copy_dir_recursive(&source_dir, &mcp_server_dir, &is_bundle_file, symlinks, &mut || {
+                files_copied += 1;
+                emit_install_progress(
+                    &app_handle,
+                    InstallPhase::CopyingFiles,
+                    Some((files_copied, total_files)),
+                );
+            })
+            .map_err(|e| AppError::io("Failed to copy MCP server files", e))?;
+            manifest::write_manifest(&source_dir, &mcp_server_dir, &is_bundle_file)
+                .map_err(|e| AppError::io("Failed to write MCP server manifest", e))?;
+            Ok(mcp_server_dir)
+        })
+        .await??
+    };
+
+    // Install dependencies
+    check_cancelled(&cancel)?;
+    emit_install_progress(&app_handle, InstallPhase::InstallingDependencies, None);
+    let attempts = run_bun_install(&app_handle, &mcp_server_dir, false, &cancel).await?;
+
+    // Only record the version once the install is complete
+    if let Some(version) = &bundle_version {
+        write_installed_version(&mcp_server_dir, version, plan.variant.as_deref())?;
+    }
+
+    emit_install_progress(&app_handle, InstallPhase::Done, None);
+
+    Ok(format!(
+        "MCP Server installed successfully to {} (bun install took {} attempt{})",
+        mcp_server_dir.display(),
+        attempts,
+        if attempts == 1 { "" } else { "s" }
+    ))
+}
+
+// Helper function to run `bun install` in an MCP server install, retrying transient
+// network failures. `force` passes --force to refetch every package instead of trusting
+// the cache. Returns how many attempts it took.
+async fn run_bun_install(
+    app_handle: &tauri::AppHandle,
+    mcp_server_dir: &std::path::Path,
+    force: bool,
+    cancel: &CancellationToken,
+) -> Result<u32, AppError> {
+    let bun_path = get_bun_path()?;
+    let config = server_config(app_handle);
+    let _npmrc = match &config.bun_registry {
+        Some(registry) => {
+            log::info!("bun install using registry {}", registry);
+            Some(
+                RegistryNpmrc::write(mcp_server_dir, registry, config.bun_registry_token.as_deref())
+                    .map_err(|e| AppError::io("Failed to write registry .npmrc", e))?,
+            )
+        }
+        None => None,
+    };
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let mut bun_install = tokio::process::Command::new(&bun_path);
+        bun_install.args(["install"]).current_dir(mcp_server_dir);
+        if force {
+            bun_install.arg("--force");
+        }
+        let (status, stderr) = stream_bun_install(app_handle, bun_install, cancel).await?;
+
+        // Success is decided by the exit status alone; the streamed counts are only for display
+        if status.success() {
+            // bun can exit 0 without installing anything, e.g. on a lockfile mismatch
+            verify_node_modules(mcp_server_dir)?;
+            return Ok(attempts);
+        }
+
+        // Only network hiccups are worth retrying; a bad package.json fails the same way every time
+        let retryable = RETRYABLE_NETWORK_ERRORS
+            .iter()
+            .any(|marker| stderr.contains(marker));
+        if !retryable || attempts >= BUN_INSTALL_ATTEMPTS {
+            return Err(AppError::CommandFailed {
+                command: format!("bun install (attempt {} of {})", attempts, BUN_INSTALL_ATTEMPTS),
+                stderr,
+            });
+        }
+
+        log::warn!(
+            "bun install failed with a network error (attempt {} of {}), retrying: {}",
+            attempts,
+            BUN_INSTALL_ATTEMPTS,
+            stderr.trim()
+        );
+        tokio::select! {
+            _ = tokio::time::sleep(BUN_INSTALL_RETRY_DELAY) => {}
+            _ = cancel.cancelled() => return Err(AppError::OperationCancelled),
+        }
+    }
+}
+
+// A temporary .npmrc pointing `bun install` at a custom registry. Whatever .npmrc the
+// install had before is put back when this is dropped, so the token never outlives the install.
+struct RegistryNpmrc {
+    path: std::path::PathBuf,
+    previous: Option<Vec<u8>>,
+}
+
+impl RegistryNpmrc {
+    fn write(dir: &std::path::Path, registry: &str, token: Option<&str>) -> std::io::Result<Self> {
+        let path = dir.join(".npmrc");
+        let previous = match std::fs::read(&path) {
+            Ok(contents) => Some(contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+
+        let registry = format!("{}/", registry.trim_end_matches('/'));
+        let mut contents = format!("registry={}\n", registry);
+        if let Some(token) = token {
+            // Tokens are scoped to the registry URL without its scheme
+            let scope = registry.split_once("://").map_or(registry.as_str(), |(_, rest)| rest);
+            contents.push_str(&format!("//{}:_authToken={}\n", scope, token));
+        }
+        std::fs::write(&path, contents)?;
+        Ok(RegistryNpmrc { path, previous })
+    }
+}
+
+impl Drop for RegistryNpmrc {
+    fn drop(&mut self) {
+        let result = match &self.previous {
+            Some(contents) => std::fs::write(&self.path, contents),
+            None => std::fs::remove_file(&self.path),
+        };
+        if let Err(e) = result {
+            log::warn!("Failed to clean up {:?}: {}", self.path, e);
+        }
+    }
+}
+
+// Helper function to run bun install, forwarding each output line to the frontend as
+// install progress. Returns the exit status and the collected stderr.
+async fn stream_bun_install(
+    app_handle: &tauri::AppHandle,
+    mut command: tokio::process::Command,
+    cancel: &CancellationToken,
+) -> Result<(std::process::ExitStatus, String), AppError> {
+    use tokio::io::AsyncBufReadExt;
+
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| AppError::spawn("bun install", e))?;
+    let mut stdout = child.stdout.take().map(|out| tokio::io::BufReader::new(out).lines());
+    let mut stderr = child.stderr.take().map(|err| tokio::io::BufReader::new(err).lines());
+    let mut stderr_text = String::new();
+
+    // Returning early drops the child, and kill_on_drop kills it
+    while stdout.is_some() || stderr.is_some() {
+        tokio::select! {
+            line = async { stdout.as_mut().unwrap().next_line().await }, if stdout.is_some() => {
+                match line {
+                    Ok(Some(line)) => emit_bun_install_line(app_handle, line),
+                    _ => stdout = None,
+                }
+            }
+            line = async { stderr.as_mut().unwrap().next_line().await }, if stderr.is_some() => {
+                match line {
+                    Ok(Some(line)) => {
+                        stderr_text.push_str(&line);
+                        stderr_text.push('\n');
+                        emit_bun_install_line(app_handle, line);
+                    }
+                    _ => stderr = None,
+                }
+            }
+            _ = cancel.cancelled() => return Err(AppError::OperationCancelled),
+        }
+    }
+
+    let status = tokio::select! {
+        status = child.wait() => status,
+        _ = cancel.cancelled() => return Err(AppError::OperationCancelled),
+    };
+    let status = status.map_err(|e| AppError::io("Failed to wait for bun install", e))?;
+    Ok((status, stderr_text))
+}
+
+// Helper function to pull a package count out of bun's summary lines, such as
+// "Resolved, downloaded and extracted [312]" or "45 packages installed [1.20s]"
+fn parse_bun_package_count(line: &str) -> Option<u64> {
+    if line.starts_with("Resolved") || line.starts_with("Saved") {
+        let count = line.rsplit('[').next()?.trim_end_matches(']');
+        return count.trim().parse().ok();
+    }
+    let (count, rest) = line.trim().split_once(' ')?;
+    if rest.starts_with("package") {
+        return count.parse().ok();
+    }
+    None
+}
+
+fn emit_bun_install_line(app_handle: &tauri::AppHandle, line: String) {
+    let progress = InstallProgress {
+        phase: InstallPhase::InstallingDependencies,
+        files_copied: None,
+        total_files: None,
+        packages: parse_bun_package_count(&line),
+        output_line: Some(line),
+    };
+    if let Err(e) = app_handle.emit("mcp-install-progress", progress) {
+        log::warn!("Failed to emit install progress: {}", e);
+    }
+}
+
+// Upgrade an existing install in place from the bundled MCP server. Only files whose
+// checksum differs from the installed manifest are copied, so user files are left alone.
+#[tauri::command]
+async fn update_mcp_server(
+    app_handle: tauri::AppHandle,
+    install_dir: Option<String>,
+) -> Result<McpUpdateSummary, AppError> {
+    let _install_lock = acquire_install_lock(&app_handle).await?;
+    let cancel = begin_operation(&app_handle);
+    let mcp_server_dir = mcp_server_dir(&app_handle, install_dir.as_deref())?;
+    if !mcp_server_dir.join("server.ts").is_file() {
+        return Err(AppError::ServerNotInstalled);
+    }
+    // Updates stay on whichever variant is installed
+    let variant = read_installed_variant(&mcp_server_dir);
+    let (plan, bundle_hashes) = {
+        let app_handle = app_handle.clone();
+        let install_dir = install_dir.clone();
+        run_blocking(move || -> Result<_, AppError> {
+            let plan = plan_install(&app_handle, install_dir.as_deref(), false, variant.as_deref())?;
+            let source_dir = std::path::Path::new(&plan.source_dir);
+            let bundle_hashes = manifest::hash_bundle(source_dir, &is_bundle_file)
+                .map_err(|e| AppError::io("Failed to hash MCP server bundle", e))?;
+            Ok((plan, bundle_hashes))
+        })
+        .await??
+    };
+    let source_dir = std::path::PathBuf::from(&plan.source_dir);
+
+    // Without a manifest every bundled file is treated as changed
+    let installed_hashes = manifest::read_manifest(&mcp_server_dir).unwrap_or_default();
+    let changed: Vec<String> = bundle_hashes
+        .iter()
+        .filter(|(relative, hash)| {
+            installed_hashes.get(*relative) != Some(*hash)
+                || !mcp_server_dir.join(relative.as_str()).is_file()
+        })
+        .map(|(relative, _)| relative.clone())
+        .collect();
+    let package_changed = changed.iter().any(|relative| relative.as_str() == "package.json");
+    let dependencies_needed = package_changed || !mcp_server_dir.join("node_modules").is_dir();
+
+    if changed.is_empty() && !dependencies_needed {
+        return Ok(McpUpdateSummary {
+            files_updated: 0,
+            dependencies_installed: false,
+            version: plan.installed_version,
+            restarted: false,
+        });
+    }
+
+    check_cancelled(&cancel)?;
+    let was_running = {
+        let mcp_process = app_handle.state::<McpProcess>();
+        let processes = mcp_process.lock().unwrap();
+        processes.server.is_some() || processes.socket.is_some()
+    };
+    if was_running {
+        let stopped = stop_mcp_processes_async(&app_handle).await?;
+        log::info!("Stopped MCP processes {:?} before updating", stopped);
+    }
+
+    let files_updated = changed.len();
+    {
+        let app_handle = app_handle.clone();
+        let mcp_server_dir = mcp_server_dir.clone();
+        run_blocking(move || -> Result<(), AppError> {
+            let total_files = changed.len() as u64;
+            emit_install_progress(&app_handle, InstallPhase::CopyingFiles, Some((0, total_files)));
+            for (copied, relative) in changed.iter().enumerate() {
+                let destination = mcp_server_dir.join(relative.as_str());
+                if let Some(parent) = destination.parent() {
+                    std::fs::create_dir_all(parent)
+                        .map_err(|e| AppError::io(format!("Failed to create {:?}", parent), e))?;
+                }
+                std::fs::copy(source_dir.join(relative.as_str()), &destination)
+                    .map_err(|e| AppError::io(format!("Failed to update {}", relative), e))?;
+                emit_install_progress(
+                    &app_handle,
+                    InstallPhase::CopyingFiles,
+                    Some((copied as u64 + 1, total_files)),
+                );
+            }
+            manifest::save_manifest(&mcp_server_dir, &bundle_hashes)
+                .map_err(|e| AppError::io("Failed to write MCP server manifest", e))
+        })
+        .await??;
+    }
+
+    if dependencies_needed {
+        check_cancelled(&cancel)?;
+        emit_install_progress(&app_handle, InstallPhase::InstallingDependencies, None);
+        run_bun_install(&app_handle, &mcp_server_dir, false, &cancel).await?;
+    }
+
+    if let Some(version) = &plan.bundle_version {
+        write_installed_version(&mcp_server_dir, version, plan.variant.as_deref())?;
+    }
+    emit_install_progress(&app_handle, InstallPhase::Done, None);
+    log::info!("Updated {} MCP server file(s) in {:?}", files_updated, mcp_server_dir);
+
+    if was_running {
+        start_mcp_server(app_handle.clone(), install_dir).await?;
+    }
+
+    Ok(McpUpdateSummary {
+        files_updated,
+        dependencies_installed: dependencies_needed,
+        version: plan.bundle_version,
+        restarted: was_running,
+    })
+}
+
+// Rerun `bun install` in an existing install without recopying the bundle, e.g. when
+// node_modules is corrupted. Source files are left untouched.
+#[tauri::command]
+async fn reinstall_mcp_dependencies(
+    app_handle: tauri::AppHandle,
+    install_dir: Option<String>,
+    force: Option<bool>,
+) -> Result<DependencyReinstall, AppError> {
+    let _install_lock = acquire_install_lock(&app_handle).await?;
+    let cancel = begin_operation(&app_handle);
+    let mcp_server_dir = mcp_server_dir(&app_handle, install_dir.as_deref())?;
+    let installed = mcp_server_dir.join("server.ts").is_file()
+        && mcp_server_dir.join("package.json").is_file();
+    if !installed {
+        return Err(AppError::ServerNotInstalled);
+    }
+
+    let force = force.unwrap_or(false);
+    log::info!("Reinstalling MCP server dependencies in {:?} (force: {})", mcp_server_dir, force);
+    emit_install_progress(&app_handle, InstallPhase::InstallingDependencies, None);
+    let attempts = run_bun_install(&app_handle, &mcp_server_dir, force, &cancel).await?;
+    emit_install_progress(&app_handle, InstallPhase::Done, None);
+
+    Ok(DependencyReinstall { force, attempts })
+}
+
+// Compare the shipped bundle's version against the installed one without changing
+// anything; the read-only counterpart to update_mcp_server
+#[tauri::command]
+async fn check_mcp_update(
+    app_handle: tauri::AppHandle,
+    install_dir: Option<String>,
+) -> Result<UpdateInfo, AppError> {
+    let mcp_server_dir = mcp_server_dir(&app_handle, install_dir.as_deref())?;
+    let bundle_dir = resolve_bundle_dir(&app_handle, "mcp-server-bundle").ok_or_else(|| {
+        AppError::not_found("MCP server bundle in resources or development path")
+    })?;
+
+    // Updates stay on whichever variant is installed, so compare against that build
+    let source_dir = match read_installed_variant(&mcp_server_dir) {
+        Some(variant) => bundle_dir.join(variant),
+        None => bundle_dir,
+    };
+    let current = read_installed_version(&mcp_server_dir);
+    let available = read_package_version(&source_dir);
+
+    let update_available = match (&current, &available) {
+        (Some(current), Some(available)) => match (parse_semver(current), parse_semver(available)) {
+            (Some(current), Some(available)) => available > current,
+            // Not SemVer; any difference is worth offering
+            _ => current != available,
+        },
+        _ => false,
+    };
+
+    Ok(UpdateInfo {
+        current,
+        available,
+        update_available,
+    })
+}
+
+// `bun install` is retried on these transient network errors
+const RETRYABLE_NETWORK_ERRORS: &[&str] = &["ETIMEDOUT", "ECONNRESET", "ECONNREFUSED", "EAI_AGAIN"];
+const BUN_INSTALL_ATTEMPTS: u32 = 3;
+const BUN_INSTALL_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(2);
+
+// Records which bundle version is installed
+const VERSION_FILE: &str = "version.json";
+
+fn write_installed_version(
+    mcp_server_dir: &std::path::Path,
+    version: &str,
+    variant: Option<&str>,
+) -> Result<(), AppError> {
+    let version_json = serde_json::json!({ "version": version, "variant": variant }).to_string();
+    std::fs::write(mcp_server_dir.join(VERSION_FILE), version_json)
+        .map_err(|e| AppError::io("Failed to write MCP server version.json", e))
+}
+
+// Helper function to name the host the way package.json `os`/`cpu` fields do
+fn npm_platform() -> (&'static str, &'static str) {
+    let os = match std::env::consts::OS {
+        "macos" => "darwin",
+        "windows" => "win32",
+        other => other,
+    };
+    let cpu = match std::env::consts::ARCH {
+        "x86_64" => "x64",
+        "aarch64" => "arm64",
+        "x86" => "ia32",
+        other => other,
+    };
+    (os, cpu)
+}
+
+// Helper function to apply an npm `os`/`cpu` list, which may allow ("arm64") or exclude ("!arm64")
+fn platform_allowed(allowed: &[String], host: &str) -> bool {
+    if allowed.iter().any(|entry| entry.strip_prefix('!') == Some(host)) {
+        return false;
+    }
+    let positives: Vec<&String> = allowed.iter().filter(|entry| !entry.starts_with('!')).collect();
+    positives.is_empty() || positives.iter().any(|entry| entry.as_str() == host)
+}
+
+// Helper function to reject a bundle whose package.json `os`/`cpu` markers exclude this
+// host, before bun install fails on its native modules. Bundles without markers pass.
+fn check_bundle_platform(bundle_dir: &std::path::Path) -> Result<(), AppError> {
+    let Some(package) = std::fs::read_to_string(bundle_dir.join("package.json"))
+        .ok()
+        .and_then(|contents| serde_json::from_str::<serde_json::Value>(&contents).ok())
+    else {
+        return Ok(());
+    };
+    let markers = |field: &str| -> Vec<String> {
+        package
+            .get(field)
+            .and_then(|value| value.as_array())
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|value| value.as_str().map(|value| value.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let (os_markers, cpu_markers) = (markers("os"), markers("cpu"));
+
+    let (host_os, host_cpu) = npm_platform();
+    if platform_allowed(&os_markers, host_os) && platform_allowed(&cpu_markers, host_cpu) {
+        return Ok(());
+    }
+    Err(AppError::ArchMismatch {
+        bundle: format!("os {:?}, cpu {:?}", os_markers, cpu_markers),
+        host: format!("{}-{}", host_os, host_cpu),
+    })
+}
+
+// Helper function to check every top-level dependency in package.json landed in node_modules
+fn verify_node_modules(mcp_server_dir: &std::path::Path) -> Result<(), AppError> {
+    let node_modules = mcp_server_dir.join("node_modules");
+    let dependencies: Vec<String> = std::fs::read_to_string(mcp_server_dir.join("package.json"))
+        .ok()
+        .and_then(|contents| serde_json::from_str::<serde_json::Value>(&contents).ok())
+        .and_then(|package| {
+            package
+                .get("dependencies")
+                .and_then(|deps| deps.as_object())
+                .map(|deps| deps.keys().cloned().collect())
+        })
+        .unwrap_or_default();
+
+    // Scoped packages ("@scope/name") are nested directories, which join handles
+    let missing: Vec<String> = if node_modules.is_dir() {
+        dependencies
+            .into_iter()
+            .filter(|name| !node_modules.join(name).is_dir())
+            .collect()
+    } else {
+        vec!["node_modules".to_string()]
+    };
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::DependenciesMissing { missing })
+    }
+}
+
+// Helper function to read the `version` field of a bundle's package.json
+fn read_package_version(bundle_dir: &std::path::Path) -> Option<String> {
+    let contents = std::fs::read_to_string(bundle_dir.join("package.json")).ok()?;
+    let package: serde_json::Value = serde_json::from_str(&contents).ok()?;
+    package.get("version")?.as_str().map(|version| version.to_string())
+}
+
+// Name, version, description and script names from the installed package.json, for the UI
+#[tauri::command]
+async fn get_mcp_package_info(
+    app_handle: tauri::AppHandle,
+    install_dir: Option<String>,
+) -> Result<PackageInfo, AppError> {
+    #[derive(Deserialize)]
+    struct PackageJson {
+        name: Option<String>,
+        version: Option<String>,
+        description: Option<String>,
+        #[serde(default)]
+        scripts: std::collections::BTreeMap<String, serde_json::Value>,
+    }
+
+    let package_file = mcp_server_dir(&app_handle, install_dir.as_deref())?.join("package.json");
+    let contents = match std::fs::read_to_string(&package_file) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(AppError::ServerNotInstalled)
+        }
+        Err(e) => return Err(AppError::io(format!("Failed to read {:?}", package_file), e)),
+    };
+    let package: PackageJson = serde_json::from_str(&contents)
+        .map_err(|e| AppError::io(format!("Failed to parse {:?}", package_file), e))?;
+
+    Ok(PackageInfo {
+        name: package.name,
+        version: package.version,
+        description: package.description,
+        scripts: package.scripts.into_keys().collect(),
+    })
+}
+
+// Helper function to read a field of the version.json written by the last successful install
+fn read_version_file_field(mcp_server_dir: &std::path::Path, field: &str) -> Option<String> {
+    let contents = std::fs::read_to_string(mcp_server_dir.join(VERSION_FILE)).ok()?;
+    let version: serde_json::Value = serde_json::from_str(&contents).ok()?;
+    version.get(field)?.as_str().map(|value| value.to_string())
+}
+
+fn read_installed_version(mcp_server_dir: &std::path::Path) -> Option<String> {
+    read_version_file_field(mcp_server_dir, "version")
+}
+
+// None for the default (top-level) bundle
+fn read_installed_variant(mcp_server_dir: &std::path::Path) -> Option<String> {
+    read_version_file_field(mcp_server_dir, "variant")
+}
+
+#[tauri::command]
+async fn get_installed_mcp_version(
+    app_handle: tauri::AppHandle,
+    install_dir: Option<String>,
+) -> Result<InstalledMcpVersion, AppError> {
+    let mcp_server_dir = mcp_server_dir(&app_handle, install_dir.as_deref())?;
+    Ok(InstalledMcpVersion {
+        version: read_installed_version(&mcp_server_dir),
+        variant: read_installed_variant(&mcp_server_dir),
+    })
+}
+
+#[tauri::command]
+async fn start_mcp_server(
+    app_handle: tauri::AppHandle,
+    install_dir: Option<String>,
+) -> Result<String, AppError> {
+    let mcp_server_dir = mcp_server_dir(&app_handle, install_dir.as_deref())?;
+    
+    if !mcp_server_dir.exists() {
+        return Err(AppError::ServerNotInstalled);
+    }
+
+    // Check if already running (or being started by a concurrent call)
+    let mcp_process: McpProcess = app_handle.state::<McpProcess>().inner().clone();
+    {
+        let mut processes = mcp_process.lock().unwrap();
+        if processes.starting {
+            return Ok("MCP server is already starting".to_string());
+        }
+        if let Some(child) = processes.server.as_mut() {
+            if let Ok(None) = child.try_wait() {
+                return Ok("MCP server is already running".to_string());
+            }
+        }
+        processes.starting = true;
+    }
+    let _start_guard = McpStartGuard(mcp_process.clone());
+
+    // Get Bun path
+    let bun_path = get_bun_path()?;
+    let checked_path = bun_path.clone();
+    run_blocking(move || check_bun_compatible(&checked_path)).await??;
+
+    // Another app instance may already own the server
+    if let Some(owner_pid) = read_mcp_lock().filter(|pid| process_alive(*pid)) {
+        return Err(AppError::AlreadyRunning { pid: owner_pid });
+    }
+
+    let config = server_config(&app_handle);
+
+    // A stale server on either port would make the new one exit immediately
+    for port in [config.mcp_port, config.socket_port] {
+        if !port_available(port) {
+            return Err(AppError::PortInUse { port });
+        }
+    }
+
+    // Start the MCP server
+    if !config.env.is_empty() {
+        log::info!("MCP server environment overrides: {:?}", redact_env(&config.env));
+    }
+    let mut child = spawn_mcp_server_process(&app_handle, &bun_path, &mcp_server_dir, &config)?;
+    let pid = child.id();
+    
+    // Start socket server as well
+    let socket_path = mcp_server_dir.join("socket.ts");
+    let mut socket_child = match Command::new(&bun_path)
+        .args(["run", socket_path.to_str().unwrap()])
+        .current_dir(&mcp_server_dir)
+        .envs(&config.env)
+        .env("PORT", config.socket_port.to_string())
+        .env("NODE_ENV", config.mode.env_value())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(mut socket_child) => {
+            capture_child_output(&app_handle, &mut socket_child, "socket-server");
+            socket_child
+        }
+        Err(e) => {
+            // Don't leave a half-started server behind without a handle to it
+            let _ = run_blocking(move || terminate_child(&mut child, SHUTDOWN_GRACE_PERIOD)).await;
+            return Err(AppError::spawn("socket server", e));
+        }
+    };
+
+    // Only report success once both processes survived startup and the socket answers
+    let ready = wait_for_mcp_ready(&app_handle, &mut child, &mut socket_child, config.socket_port);
+    if let Err(e) = ready.await {
+        let _ = run_blocking(move || {
+            let _ = terminate_child(&mut child, SHUTDOWN_GRACE_PERIOD);
+            let _ = terminate_child(&mut socket_child, SHUTDOWN_GRACE_PERIOD);
+        })
+        .await;
+        return Err(e);
+    }
+
+    // Store both processes so they can be stopped later
+    {
+        let mut processes = mcp_process.lock().unwrap();
+        processes.server = Some(child);
+        processes.socket = Some(socket_child);
+        processes.install_dir = install_dir;
+    }
+    set_desired_running(&app_handle, ManagedServer::Mcp, true);
+    write_mcp_lock(pid);
+
+    Ok(format!("MCP Server started with PID: {}", pid))
+}
+
+// Helper function to spawn server.ts on its own, without the socket server
+fn spawn_mcp_server_process(
+    app_handle: &tauri::AppHandle,
+    bun_path: &str,
+    mcp_server_dir: &std::path::Path,
+    config: &ServerConfig,
+) -> Result<Child, AppError> {
+    let server_path = mcp_server_dir.join("server.ts");
+    let mut child = Command::new(bun_path)
+        .args(["run", server_path.to_str().unwrap()])
+        .args(&config.mcp_args)
+        .current_dir(mcp_server_dir)
+        .envs(&config.env)
+        .env("PORT", config.mcp_port.to_string())
+        .env("SOCKET_PORT", config.socket_port.to_string())
+        .env("NODE_ENV", config.mode.env_value())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AppError::spawn("MCP server", e))?;
+    capture_child_output(app_handle, &mut child, "mcp-server");
+    Ok(child)
+}
+
+// Stop the MCP server and its socket server, returning the PIDs that were terminated.
+// An empty list means neither was running.
+#[tauri::command]
+async fn stop_mcp_server(app_handle: tauri::AppHandle) -> Result<Vec<u32>, AppError> {
+    let stopped = stop_mcp_processes_async(&app_handle).await?;
+    if stopped.is_empty() {
+        log::info!("MCP server is not running");
+    } else {
+        log::info!("Stopped MCP processes {:?}", stopped);
+    }
+    Ok(stopped)
+}
+
+// Deeper readiness check than a socket probe: start a throwaway instance of server.ts,
+// speak MCP to it over stdio (initialize, then tools/list) and report how many tools it
+// registered. The running server can't be asked directly, since its stdio belongs to the
+// client that launched it.
+#[tauri::command]
+async fn check_mcp_ready(
+    app_handle: tauri::AppHandle,
+    install_dir: Option<String>,
+    timeout_secs: Option<u64>,
+) -> Result<McpReadiness, AppError> {
+    let mcp_server_dir = mcp_server_dir(&app_handle, install_dir.as_deref())?;
+    if !mcp_server_dir.join("server.ts").exists() {
+        return Err(AppError::ServerNotInstalled);
+    }
+    let bun_path = get_bun_path()?;
+    let checked_path = bun_path.clone();
+    run_blocking(move || check_bun_compatible(&checked_path)).await??;
+    let config = server_config(&app_handle);
+    let timeout_secs = timeout_secs.unwrap_or(MCP_PROBE_TIMEOUT_SECS);
+
+    let mut child = tokio::process::Command::new(&bun_path)
+        .args(["run", "server.ts"])
+        .args(&config.mcp_args)
+        .current_dir(&mcp_server_dir)
+        .envs(&config.env)
+        .env("PORT", config.mcp_port.to_string())
+        .env("SOCKET_PORT", config.socket_port.to_string())
+        .env("NODE_ENV", config.mode.env_value())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| AppError::spawn("MCP server probe", e))?;
+    let stderr = child.stderr.take();
+    let stderr_text = tauri::async_runtime::spawn(async move {
+        use tokio::io::AsyncReadExt;
+        let mut text = String::new();
+        if let Some(mut stderr) = stderr {
+            let _ = stderr.read_to_string(&mut text).await;
+        }
+        text
+    });
+
+    let started = std::time::Instant::now();
+    let exchange = tokio::time::timeout(
+        std::time::Duration::from_secs(timeout_secs),
+        mcp_probe_exchange(&mut child),
+    )
+    .await;
+    // Stop the probe so its stderr reaches EOF
+    let _ = child.start_kill();
+    let _ = child.wait().await;
+    let stderr_tail = |text: String| {
+        let lines: Vec<&str> = text.lines().collect();
+        lines[lines.len().saturating_sub(STDERR_TAIL_LINES)..].join("\n")
+    };
+
+    match exchange {
+        Ok(Ok(mut readiness)) => {
+            readiness.elapsed_ms = started.elapsed().as_millis() as u64;
+            log::info!(
+                "MCP server answered tools/list with {} tools in {}ms",
+                readiness.tool_count,
+                readiness.elapsed_ms
+            );
+            Ok(readiness)
+        }
+        Ok(Err(reason)) => Err(AppError::HealthCheckFailed {
+            reason: match stderr_tail(stderr_text.await.unwrap_or_default()) {
+                tail if tail.is_empty() => reason,
+                tail => format!("{}\n{}", reason, tail),
+            },
+        }),
+        Err(_) => Err(AppError::Timeout {
+            operation: "Waiting for the MCP server to answer initialize and tools/list"
+                .to_string(),
+            seconds: timeout_secs,
+            stderr: stderr_tail(stderr_text.await.unwrap_or_default()),
+        }),
+    }
+}
+
+// Helper function to run the initialize handshake and page through tools/list over the
+// child's stdio. Messages are newline-delimited JSON-RPC; non-JSON lines are skipped.
+async fn mcp_probe_exchange(child: &mut tokio::process::Child) -> Result<McpReadiness, String> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+
+    let mut stdin = child.stdin.take().ok_or("MCP server stdin is not piped")?;
+    let stdout = child.stdout.take().ok_or("MCP server stdout is not piped")?;
+    let mut lines = tokio::io::BufReader::new(stdout).lines();
+
+    let mut next_id = 0u64;
+    let mut request = |method: &str, params: serde_json::Value| {
+        next_id += 1;
+        let message = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": next_id,
+            "method": method,
+            "params": params,
+        });
+        (next_id, format!("{}\n", message))
+    };
+
+    let (id, message) = request(
+        "initialize",
+        serde_json::json!({
+            "protocolVersion": MCP_PROTOCOL_VERSION,
+            "capabilities": {},
+            "clientInfo": { "name": "wirecraft", "version": env!("CARGO_PKG_VERSION") },
+        }),
+    );
+    stdin
+        .write_all(message.as_bytes())
+        .await
+        .map_err(|e| format!("Failed to send initialize: {}", e))?;
+    let initialized = read_mcp_response(&mut lines, id, "initialize").await?;
+    let server_info = initialized.get("serverInfo");
+    let field =
+        |value: Option<&serde_json::Value>| value.and_then(|v| v.as_str()).map(str::to_string);
+    let mut readiness = McpReadiness {
+        server_name: field(server_info.and_then(|info| info.get("name"))),
+        server_version: field(server_info.and_then(|info| info.get("version"))),
+        protocol_version: field(initialized.get("protocolVersion")),
+        tool_count: 0,
+        elapsed_ms: 0,
+    };
+
+    let notification = serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/initialized",
+    });
+    stdin
+        .write_all(format!("{}\n", notification).as_bytes())
+        .await
+        .map_err(|e| format!("Failed to send initialized notification: {}", e))?;
+
+    let mut cursor: Option<String> = None;
+    loop {
+        let params = match &cursor {
+            Some(cursor) => serde_json::json!({ "cursor": cursor }),
+            None => serde_json::json!({}),
+        };
+        let (id, message) = request("tools/list", params);
+        stdin
+            .write_all(message.as_bytes())
+            .await
+            .map_err(|e| format!("Failed to send tools/list: {}", e))?;
+        let result = read_mcp_response(&mut lines, id, "tools/list").await?;
+        let tools = result
+            .get("tools")
+            .and_then(|tools| tools.as_array())
+            .ok_or("tools/list response has no tools array")?;
+        readiness.tool_count += tools.len();
+        cursor = field(result.get("nextCursor"));
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    if readiness.tool_count == 0 {
+        return Err("MCP server started but registered no tools".to_string());
+    }
+    Ok(readiness)
+}
+
+// Helper function to read stdout until the JSON-RPC response with `id` arrives, returning
+// its result or the error it carried
+async fn read_mcp_response(
+    lines: &mut tokio::io::Lines<tokio::io::BufReader<tokio::process::ChildStdout>>,
+    id: u64,
+    method: &str,
+) -> Result<serde_json::Value, String> {
+    loop {
+        let line = lines
+            .next_line()
+            .await
+            .map_err(|e| format!("Failed to read MCP server output: {}", e))?
+            .ok_or_else(|| format!("MCP server exited before answering {}", method))?;
+        let Ok(message) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+        if message.get("id").and_then(|v| v.as_u64()) != Some(id) {
+            continue;
+        }
+        if let Some(error) = message.get("error") {
+            return Err(format!("MCP server rejected {}: {}", method, error));
+        }
+        return message
+            .get("result")
+            .cloned()
+            .ok_or_else(|| format!("Malformed {} response: {}", method, line));
+    }
+}
+
+// Paths the last install_mcp_server and start_fastapi_server resolved, for diagnosing
+// the resource-vs-development directory lookup picking the wrong location
+#[tauri::command]
+async fn last_resolution(app_handle: tauri::AppHandle) -> Result<PathResolution, AppError> {
+    Ok(app_handle.state::<LastResolution>().lock().unwrap().clone())
+}
+
+// Reload the MCP server while the socket server, and the clients connected to it, stay up.
+// Sends SIGHUP when mcp_reload_signal says server.ts handles it; otherwise only server.ts
+// is respawned. Falls back to a full restart if the socket server isn't running either.
+#[tauri::command]
+async fn reload_mcp_server(app_handle: tauri::AppHandle) -> Result<McpReloadResult, AppError> {
+    let mcp_process: McpProcess = app_handle.state::<McpProcess>().inner().clone();
+    let (server_pid, socket_alive, install_dir) = {
+        let mut guard = mcp_process.lock().unwrap();
+        let processes = &mut *guard;
+        let alive = |child: Option<&mut Child>| {
+            child.and_then(|child| matches!(child.try_wait(), Ok(None)).then(|| child.id()))
+        };
+        (
+            alive(processes.server.as_mut()),
+            alive(processes.socket.as_mut()).is_some(),
+            processes.install_dir.clone(),
+        )
+    };
+    let Some(server_pid) = server_pid else {
+        return Err(AppError::not_found("Running MCP server"));
+    };
+
+    if !socket_alive {
+        log::info!("Socket server is down; reloading the MCP server with a full restart");
+        restart_with_new_config(&app_handle, ManagedServer::Mcp).await?;
+        let pid = mcp_process.lock().unwrap().server.as_ref().map(Child::id);
+        return Ok(McpReloadResult {
+            kind: McpReloadKind::FullRestart,
+            pid,
+        });
+    }
+
+    let config = server_config(&app_handle);
+    #[cfg(unix)]
+    if config.mcp_reload_signal {
+        if unsafe { libc::kill(server_pid as libc::pid_t, libc::SIGHUP) } != 0 {
+            return Err(AppError::io(
+                format!("Failed to signal MCP server (PID {})", server_pid),
+                std::io::Error::last_os_error(),
+            ));
+        }
+        log::info!("Sent SIGHUP to MCP server (PID {})", server_pid);
+        return Ok(McpReloadResult {
+            kind: McpReloadKind::Signal,
+            pid: Some(server_pid),
+        });
+    }
+
+    // Keep the supervisor from treating the swap as a crash
+    app_handle
+        .state::<Supervisor>()
+        .lock()
+        .unwrap()
+        .server_mut(ManagedServer::Mcp)
+        .restarting = true;
+    let _guard = RestartingGuard(app_handle.clone(), ManagedServer::Mcp);
+
+    let mcp_server_dir = mcp_server_dir(&app_handle, install_dir.as_deref())?;
+    let bun_path = get_bun_path()?;
+    let old_server = mcp_process.lock().unwrap().server.take();
+    if let Some(mut old_server) = old_server {
+        run_blocking(move || terminate_child(&mut old_server, SHUTDOWN_GRACE_PERIOD))
+            .await?
+            .map_err(|e| AppError::io("Failed to stop MCP server", e))?;
+    }
+    release_mcp_lock(server_pid);
+
+    let mut child = spawn_mcp_server_process(&app_handle, &bun_path, &mcp_server_dir, &config)?;
+    tokio::time::sleep(MCP_STARTUP_CHECK).await;
+    if let Ok(Some(status)) = child.try_wait() {
+        return Err(AppError::StartupFailed {
+            server: "MCP server".to_string(),
+            exit_code: status.code(),
+            stderr: stderr_tail(&app_handle, "mcp-server").unwrap_or_default(),
+        });
+    }
+
+    let pid = child.id();
+    mcp_process.lock().unwrap().server = Some(child);
+    set_desired_running(&app_handle, ManagedServer::Mcp, true);
+    write_mcp_lock(pid);
+    log::info!("Reloaded MCP server (PID {} -> {}); socket server kept running", server_pid, pid);
+
+    Ok(McpReloadResult {
+        kind: McpReloadKind::ServerRestart,
+        pid: Some(pid),
+    })
+}
+
+#[tauri::command]
+async fn check_mcp_server_installation(
+    app_handle: tauri::AppHandle,
+    verify: Option<bool>,
+    install_dir: Option<String>,
+) -> Result<bool, AppError> {
+    let mcp_server_dir = mcp_server_dir(&app_handle, install_dir.as_deref())?;
+    let server_file = mcp_server_dir.join("server.ts");
+    let package_file = mcp_server_dir.join("package.json");
+    
+    if !(server_file.exists() && package_file.exists()) {
+        return Ok(false);
+    }
+
+    if verify.unwrap_or(false) {
+        // A missing or unreadable manifest means the install never completed
+        return Ok(manifest::verify_manifest(&mcp_server_dir)
+            .map(|result| result.is_valid())
+            .unwrap_or(false));
+    }
+
+    Ok(true)
+}
+
+#[tauri::command]
+async fn verify_mcp_server_install(
+    app_handle: tauri::AppHandle,
+) -> Result<ManifestVerification, AppError> {
+    let mcp_server_dir = mcp_server_dir(&app_handle, None)?;
+    if !mcp_server_dir.exists() {
+        return Err(AppError::ServerNotInstalled);
+    }
+    if !mcp_server_dir.join(manifest::MANIFEST_FILE).exists() {
+        return Err(AppError::not_found("MCP server manifest"));
+    }
+
+    manifest::verify_manifest(&mcp_server_dir)
+        .map_err(|e| AppError::io("Failed to verify MCP server install", e))
+}
+
+// List what is actually on disk in the MCP server install, to diagnose partial or
+// wrong-bundle installs. node_modules is left out unless asked for.
+#[tauri::command]
+async fn list_installed_files(
+    app_handle: tauri::AppHandle,
+    install_dir: Option<String>,
+    include_node_modules: Option<bool>,
+) -> Result<Vec<FileEntry>, AppError> {
+    let mcp_server_dir = mcp_server_dir(&app_handle, install_dir.as_deref())?;
+    if !mcp_server_dir.is_dir() {
+        return Err(AppError::ServerNotInstalled);
+    }
+    let include_node_modules = include_node_modules.unwrap_or(false);
+
+    run_blocking(move || {
+        let mut entries = Vec::new();
+        collect_file_entries(&mcp_server_dir, &mcp_server_dir, include_node_modules, &mut entries)
+            .map_err(|e| AppError::io(format!("Failed to list {:?}", mcp_server_dir), e))?;
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(entries)
+    })
+    .await?
+}
+
+// Helper function to walk an install for list_installed_files. Symlinks are listed but
+// not followed.
+fn collect_file_entries(
+    root: &std::path::Path,
+    dir: &std::path::Path,
+    include_node_modules: bool,
+    entries: &mut Vec<FileEntry>,
+) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            if include_node_modules || entry.file_name() != "node_modules" {
+                collect_file_entries(root, &path, include_node_modules, entries)?;
+            }
+            continue;
+        }
+
+        let metadata = std::fs::symlink_metadata(&path)?;
+        let relative = path.strip_prefix(root).unwrap_or(&path);
+        entries.push(FileEntry {
+            path: relative.to_string_lossy().replace('\\', "/"),
+            size: metadata.len(),
+            modified: metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|since| since.as_secs()),
+        });
+    }
+    Ok(())
+}
+
+// Helper function to stop the tracked MCP and socket server processes, returning their PIDs
+fn stop_mcp_processes(app_handle: &tauri::AppHandle) -> Vec<u32> {
+    set_desired_running(app_handle, ManagedServer::Mcp, false);
+
+    let children = {
+        let mcp_process = app_handle.state::<McpProcess>();
+        let mut processes = mcp_process.lock().unwrap();
+        [processes.server.take(), processes.socket.take()]
+    };
+
+    let mut stopped = Vec::new();
+    for mut child in children.into_iter().flatten() {
+        let pid = child.id();
+        match terminate_child(&mut child, SHUTDOWN_GRACE_PERIOD) {
+            Ok(_) => {
+                release_mcp_lock(pid);
+                stopped.push(pid);
+            }
+            Err(e) => log::error!("Failed to stop MCP process {}: {}", pid, e),
+        }
+    }
+    stopped
+}
+
+#[tauri::command]
+async fn uninstall_mcp_server(app_handle: tauri::AppHandle, force: bool) -> Result<String, AppError> {
+    let _install_lock = acquire_install_lock(&app_handle).await?;
+    let mcp_server_dir = mcp_server_dir(&app_handle, None)?;
+
+    if !mcp_server_dir.exists() {
+        return Ok("MCP server is not installed".to_string());
+    }
+
+    let running = {
+        let mcp_process = app_handle.state::<McpProcess>();
+        let mut guard = mcp_process.lock().unwrap();
+        let processes = &mut *guard;
+        let running = [processes.server.as_mut(), processes.socket.as_mut()]
+            .into_iter()
+            .flatten()
+            .any(|child| matches!(child.try_wait(), Ok(None)));
+        running
+    };
+    if running {
+        if !force {
+            return Err(AppError::ServerRunning);
+        }
+        let stopped = stop_mcp_processes_async(&app_handle).await?;
+        log::info!("Stopped MCP processes {:?} before uninstalling", stopped);
+    }
+
+    let home_dir = home_directory().ok();
+    let mcp_server_dir =
+        removable_install_dir(&mcp_server_dir, &wirecraft_dir()?, home_dir.as_deref())?;
+
+    let remove_dir = mcp_server_dir.clone();
+    run_blocking(move || std::fs::remove_dir_all(remove_dir))
+        .await?
+        .map_err(|e| AppError::io(format!("Failed to remove {:?}", mcp_server_dir), e))?;
+    log::info!("Removed MCP server install at {:?}", mcp_server_dir);
+
+    Ok("MCP server uninstalled".to_string())
+}
+
+// Helper function to check an install directory is safe to delete, returning it with
+// symlinks resolved. Anything inside ~/.wirecraft may go; a configured install_dir elsewhere
+// must carry the version or manifest file an install writes, so a mistyped install_dir
+// such as ~/Documents is never removed.
+fn removable_install_dir(
+    mcp_server_dir: &std::path::Path,
+    wirecraft_dir: &std::path::Path,
+    home_dir: Option<&std::path::Path>,
+) -> Result<std::path::PathBuf, AppError> {
+    let refuse = |reason: String| AppError::InvalidArgument {
+        reason: format!("Refusing to remove {:?}: {}", mcp_server_dir, reason),
+    };
+    let mcp_server_dir = mcp_server_dir
+        .canonicalize()
+        .map_err(|e| AppError::io(format!("Failed to resolve {:?}", mcp_server_dir), e))?;
+
+    if home_dir
+        .and_then(|home| home.canonicalize().ok())
+        .is_some_and(|home| mcp_server_dir == home)
+    {
+        return Err(refuse("it is the home directory".to_string()));
+    }
+
+    let in_wirecraft_dir = wirecraft_dir
+        .canonicalize()
+        .is_ok_and(|root| mcp_server_dir.starts_with(&root) && mcp_server_dir != root);
+    let has_marker = [VERSION_FILE, manifest::MANIFEST_FILE]
+        .iter()
+        .any(|marker| mcp_server_dir.join(marker).is_file());
+    if !in_wirecraft_dir && !has_marker {
+        return Err(refuse(format!(
+            "it is outside {:?} and has no {} or {}, so it wasn't created by an install",
+            wirecraft_dir,
+            VERSION_FILE,
+            manifest::MANIFEST_FILE
+        )));
+    }
+    Ok(mcp_server_dir)
+}
+
+// Entrypoints start_mcp_server runs, plus the manifest bun install needs
+const REQUIRED_BUNDLE_FILES: &[&str] = &["server.ts", "socket.ts", "package.json"];
+
+// Extra space required beyond the bundle itself, as a percentage of its size
+const DISK_SPACE_MARGIN_PERCENT: u64 = 20;
+
+// Directories in a dev bundle that are rebuilt locally and must not be copied over an install
+const COPY_IGNORED_DIRS: &[&str] = &["node_modules", ".git", ".turbo", "dist"];
+
+// Helper function to decide whether a bundle entry belongs in the install
+fn is_bundle_file(path: &std::path::Path) -> bool {
+    !(path.is_dir()
+        && path
+            .file_name()
+            .is_some_and(|name| COPY_IGNORED_DIRS.contains(&name.to_string_lossy().as_ref())))
+}
+
+// Helper function to copy directories recursively, skipping entries the predicate rejects
+fn copy_dir_recursive(
+    src: &std::path::Path,
+    dst: &std::path::Path,
+    include: &dyn Fn(&std::path::Path) -> bool,
+    symlinks: SymlinkPolicy,
+    on_file_copied: &mut dyn FnMut(),
+) -> std::io::Result<()> {
+    if !dst.exists() {
+        std::fs::create_dir_all(dst)?;
+    }
+    
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let src_path = entry.path();
+        let dst_path = dst.join(entry.file_name());
+
+        if !include(&src_path) {
+            continue;
+        }
+        
+        // DirEntry::file_type doesn't follow links, so this catches symlinked directories too
+        if file_type.is_symlink() {
+            if symlinks == SymlinkPolicy::Preserve {
+                copy_symlink(&src_path, &dst_path)?;
+            }
+        } else if file_type.is_dir() {
+            copy_dir_recursive(&src_path, &dst_path, include, symlinks, on_file_copied)?;
+        } else {
+            std::fs::copy(&src_path, &dst_path)?;
+            on_file_copied();
+        }
+    }
+    
+    Ok(())
+}
+
+// Helper function to recreate a symlink at `dst` with the same target as `src`
+fn copy_symlink(src: &std::path::Path, dst: &std::path::Path) -> std::io::Result<()> {
+    let target = std::fs::read_link(src)?;
+    // Replace whatever a previous install left at this path
+    if let Ok(existing) = dst.symlink_metadata() {
+        if existing.is_dir() {
+            std::fs::remove_dir_all(dst)?;
+        } else {
+            std::fs::remove_file(dst)?;
+        }
+    }
+
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(&target, dst)
+    }
+    #[cfg(windows)]
+    {
+        // Windows needs to know the kind of link up front; a dangling link becomes a file link
+        if std::fs::metadata(src).is_ok_and(|metadata| metadata.is_dir()) {
+            std::os::windows::fs::symlink_dir(&target, dst)
+        } else {
+            std::os::windows::fs::symlink_file(&target, dst)
+        }
+    }
+}
+
+// Helper function to count files under a directory, for copy progress totals
+fn count_files(
+    dir: &std::path::Path,
+    include: &dyn Fn(&std::path::Path) -> bool,
+) -> std::io::Result<u64> {
+    let mut count = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if !include(&entry.path()) {
+            continue;
+        }
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            count += count_files(&entry.path(), include)?;
+        } else if !file_type.is_symlink() {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+// Helper function to total the size of the files a copy would write
+fn bundle_size(
+    dir: &std::path::Path,
+    include: &dyn Fn(&std::path::Path) -> bool,
+) -> std::io::Result<u64> {
+    let mut size = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if !include(&entry.path()) {
+            continue;
+        }
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            size += bundle_size(&entry.path(), include)?;
+        } else if !file_type.is_symlink() {
+            size += entry.metadata()?.len();
+        }
+    }
+    Ok(size)
+}
+
+// Helper function to check the target filesystem can hold the bundle, with headroom
+// for the node_modules bun install adds afterwards
+fn check_disk_space(source_dir: &std::path::Path, target_dir: &std::path::Path) -> Result<(), AppError> {
+    let bundle_bytes = bundle_size(source_dir, &is_bundle_file)
+        .map_err(|e| AppError::io("Failed to measure MCP server bundle", e))?;
+    let required_bytes = bundle_bytes + bundle_bytes / 100 * DISK_SPACE_MARGIN_PERCENT;
+
+    // The install directory may not exist yet, so measure its nearest existing ancestor
+    let Some(existing) = target_dir.ancestors().find(|dir| dir.exists()) else {
+        return Ok(());
+    };
+    let available_bytes = fs2::available_space(existing)
+        .map_err(|e| AppError::io(format!("Failed to read free space for {:?}", existing), e))?;
+
+    if available_bytes < required_bytes {
+        return Err(AppError::InsufficientDiskSpace {
+            required_bytes,
+            available_bytes,
+        });
+    }
+    Ok(())
+}
+
+// Helper function to report install_mcp_server progress to the frontend
+fn emit_install_progress(app_handle: &tauri::AppHandle, phase: InstallPhase, counts: Option<(u64, u64)>) {
+    let progress = InstallProgress {
+        phase,
+        files_copied: counts.map(|(copied, _)| copied),
+        total_files: counts.map(|(_, total)| total),
+        packages: None,
+        output_line: None,
+    };
+    if let Err(e) = app_handle.emit("mcp-install-progress", progress) {
+        log::warn!("Failed to emit install progress: {}", e);
+    }
+}
+
+#[tauri::command]
+async fn get_mcp_server_status(app_handle: tauri::AppHandle) -> Result<McpServerStatus, AppError> {
+    let config = server_config(&app_handle);
+    let port = config.mcp_port;
+    let (restart_count, last_exit_code, config_dirty, (started_at, uptime_secs)) = {
+        let supervisor = app_handle.state::<Supervisor>();
+        let supervisor = supervisor.lock().unwrap();
+        (
+            supervisor.mcp.restart_count,
+            supervisor.mcp.last_exit_code,
+            supervisor.mcp.config_dirty,
+            supervisor.mcp.uptime(),
+        )
+    };
+
+    // Fast check first; a bound port alone only means the server is starting
+    let port_open = std::net::TcpStream::connect(("127.0.0.1", port)).is_ok();
+    let owned_by_us = port_open && {
+        let app_handle = app_handle.clone();
+        run_blocking(move || port_owned_by_us(&app_handle, port)).await?
+    };
+    let ready = owned_by_us && probe_socket_server(port).await;
+    let socket_running = if config.socket_port == port {
+        ready
+    } else {
+        probe_socket_server(config.socket_port).await
+    };
+
+    Ok(McpServerStatus {
+        running: ready,
+        port_open,
+        port: if port_open { Some(port) } else { None },
+        socket_running,
+        owned_by_us,
+        pid: read_mcp_lock().filter(|pid| process_alive(*pid)),
+        restart_count,
+        last_exit_code,
+        mode: config.mode,
+        config_dirty,
+        started_at: started_at.filter(|_| owned_by_us),
+        uptime_secs: uptime_secs.filter(|_| owned_by_us),
+    })
+}
+
+// Helper function to check the process listening on a port is one we spawned. When the
+// listener can't be looked up, fall back to whether our processes are still alive.
+fn port_owned_by_us(app_handle: &tauri::AppHandle, port: u16) -> bool {
+    let mcp_process = app_handle.state::<McpProcess>();
+    let mut guard = mcp_process.lock().unwrap();
+    let processes = &mut *guard;
+    let our_pids: Vec<u32> = [processes.server.as_mut(), processes.socket.as_mut()]
+        .into_iter()
+        .flatten()
+        .filter_map(|child| matches!(child.try_wait(), Ok(None)).then(|| child.id()))
+        .collect();
+    drop(guard);
+
+    match find_listening_pid(port) {
+        Some(pid) => our_pids.contains(&pid),
+        None => !our_pids.is_empty(),
+    }
+}
+
+// Helper function to wait for a freshly spawned MCP server and socket server to come up.
+// The MCP server talks stdio, so for it "up" means it is still alive MCP_STARTUP_CHECK after
+// spawning and the socket answers; a syntax error in server.ts makes Bun exit well within that.
+async fn wait_for_mcp_ready(
+    app_handle: &tauri::AppHandle,
+    server: &mut Child,
+    socket: &mut Child,
+    socket_port: u16,
+) -> Result<(), AppError> {
+    let spawned_at = std::time::Instant::now();
+    let deadline = spawned_at + MCP_READY_TIMEOUT;
+
+    while std::time::Instant::now() < deadline {
+        for (child, process, log_name) in [
+            (&mut *server, "MCP server", "mcp-server"),
+            (&mut *socket, "socket server", "socket-server"),
+        ] {
+            if let Ok(Some(status)) = child.try_wait() {
+                // Let the log readers drain the last of stderr before reading the tail
+                tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+                log::error!("{} exited during startup ({})", process, status);
+                return Err(AppError::StartupFailed {
+                    server: process.to_string(),
+                    exit_code: status.code(),
+                    stderr: stderr_tail(app_handle, log_name).unwrap_or_default(),
+                });
+            }
+        }
+
+        if spawned_at.elapsed() >= MCP_STARTUP_CHECK && probe_socket_server(socket_port).await {
+            log::info!("Socket server ready on port {}", socket_port);
+            return Ok(());
+        }
+        tokio::time::sleep(MCP_READY_POLL_INTERVAL).await;
+    }
+
+    Err(AppError::Timeout {
+        operation: "Starting the socket server".to_string(),
+        seconds: MCP_READY_TIMEOUT.as_secs(),
+        stderr: stderr_tail(app_handle, "socket-server").unwrap_or_default(),
+    })
+}
+
+// Helper function to check the socket server is serving requests, not just listening.
+// Plain HTTP requests to it get a fixed banner instead of a WebSocket upgrade.
+async fn probe_socket_server(port: u16) -> bool {
+    let client = match reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(2))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            log::warn!("Failed to create HTTP client for readiness probe: {}", e);
+            return false;
+        }
+    };
+
+    match client.get(format!("http://127.0.0.1:{}/", port)).send().await {
+        Ok(response) if response.status().is_success() => response
+            .text()
+            .await
+            .map(|body| body.contains("WebSocket server running"))
+            .unwrap_or(false),
+        Ok(response) => {
+            log::debug!("Readiness probe on port {} returned {}", port, response.status());
+            false
+        }
+        Err(e) => {
+            log::debug!("Readiness probe on port {} failed: {}", port, e);
+            false
+        }
+    }
+}
+
+// A child to stop on exit; FastAPI is stopped as a process group so its reload workers go too
+struct ShutdownTarget {
+    name: &'static str,
+    child: Child,
+    process_group: bool,
+}
+
+// Stop every process we spawned so nothing is orphaned when the app exits
+fn shutdown_managed_processes(app_handle: &tauri::AppHandle) {
+    let mut targets = Vec::new();
+
+    set_desired_running(app_handle, ManagedServer::Mcp, false);
+    set_desired_running(app_handle, ManagedServer::FastApi, false);
+
+    let fastapi_child = app_handle.state::<FastAPIProcess>().lock().unwrap().take();
+    if let Some(child) = fastapi_child {
+        targets.push(ShutdownTarget {
+            name: "FastAPI server",
+            child,
+            process_group: true,
+        });
+    }
+    let mut mcp_pids = Vec::new();
+    {
+        let mcp_process = app_handle.state::<McpProcess>();
+        let mut processes = mcp_process.lock().unwrap();
+        for (name, child) in [
+            ("MCP server", processes.server.take()),
+            ("socket server", processes.socket.take()),
+        ] {
+            if let Some(child) = child {
+                mcp_pids.push(child.id());
+                targets.push(ShutdownTarget {
+                    name,
+                    child,
+                    process_group: false,
+                });
+            }
+        }
+    }
+
+    terminate_children(targets, SHUTDOWN_GRACE_PERIOD);
+    for pid in mcp_pids {
+        release_mcp_lock(pid);
+    }
+}
+
+// Helper function to stop children in parallel, so shutdown waits one grace period rather
+// than one per child. Returns the PIDs that were stopped.
+fn terminate_children(targets: Vec<ShutdownTarget>, grace_period: std::time::Duration) -> Vec<u32> {
+    let handles: Vec<_> = targets
+        .into_iter()
+        .map(|mut target| {
+            std::thread::spawn(move || {
+                let pid = target.child.id();
+                let result = if target.process_group {
+                    terminate_process_group(&mut target.child, grace_period)
+                } else {
+                    terminate_child(&mut target.child, grace_period)
+                };
+                match result {
+                    Ok(true) => log::info!("Stopped {} (PID {})", target.name, pid),
+                    Ok(false) => log::warn!("Force-killed {} (PID {})", target.name, pid),
+                    Err(e) => {
+                        log::error!("Failed to stop {} (PID {}): {}", target.name, pid, e);
+                        return None;
+                    }
+                }
+                Some(pid)
+            })
+        })
+        .collect();
+    handles
+        .into_iter()
+        .filter_map(|handle| handle.join().ok().flatten())
+        .collect()
+}
+
+// MCP server lockfile
+
+// Holds the PID of the MCP server so a second app instance doesn't start another
+fn mcp_lock_path() -> Option<std::path::PathBuf> {
+    wirecraft_dir().ok().map(|dir| dir.join("mcp-server.lock"))
+}
+
+fn read_mcp_lock() -> Option<u32> {
+    std::fs::read_to_string(mcp_lock_path()?)
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn write_mcp_lock(pid: u32) {
+    let Some(path) = mcp_lock_path() else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Err(e) = std::fs::write(&path, pid.to_string()) {
+        log::warn!("Failed to write MCP server lockfile {:?}: {}", path, e);
+    }
+}
+
+// Only removes the lockfile if it still names `pid`, so we never release another instance's lock
+fn release_mcp_lock(pid: u32) {
+    if read_mcp_lock() != Some(pid) {
+        return;
+    }
+    if let Some(path) = mcp_lock_path() {
+        if let Err(e) = std::fs::remove_file(&path) {
+            log::warn!("Failed to remove MCP server lockfile {:?}: {}", path, e);
+        }
+    }
+}
+
+// Orphaned server cleanup
+
+// Command-line fragments identifying processes this app spawns
+const OWN_PROCESS_MARKERS: &[(&str, &str)] = &[
+    ("bun", "server.ts"),
+    ("bun", "socket.ts"),
+    ("python", "main.py"),
+];
+
+// Resident memory and CPU usage of each managed process, for the resource panel
+#[tauri::command]
+async fn get_process_metrics(app_handle: tauri::AppHandle) -> Result<Vec<ProcMetric>, AppError> {
+    let tracked = {
+        let mcp_process = app_handle.state::<McpProcess>();
+        let processes = mcp_process.lock().unwrap();
+        let fastapi_process = app_handle.state::<FastAPIProcess>();
+        let fastapi = fastapi_process.lock().unwrap();
+        [
+            ("mcp", processes.server.as_ref().map(Child::id)),
+            ("socket", processes.socket.as_ref().map(Child::id)),
+            ("fastapi", fastapi.as_ref().map(Child::id)),
+        ]
+    };
+
+    run_blocking(move || {
+        use sysinfo::{Pid, ProcessRefreshKind, ProcessStatus, ProcessesToUpdate, System};
+
+        let pids: Vec<Pid> = tracked
+            .iter()
+            .filter_map(|(_, pid)| pid.map(Pid::from_u32))
+            .collect();
+        let refresh_kind = ProcessRefreshKind::new().with_memory().with_cpu();
+        let mut system = System::new();
+        // CPU usage is measured between two refreshes
+        system.refresh_processes_specifics(ProcessesToUpdate::Some(&pids), true, refresh_kind);
+        std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+        system.refresh_processes_specifics(ProcessesToUpdate::Some(&pids), true, refresh_kind);
+
+        // A PID that exited since the status check is missing from the refresh, or lingers
+        // as a zombie until the supervisor reaps it
+        tracked
+            .into_iter()
+            .map(|(name, pid)| {
+                let process = pid
+                    .and_then(|pid| system.process(Pid::from_u32(pid)))
+                    .filter(|process| process.status() != ProcessStatus::Zombie);
+                ProcMetric {
+                    name: name.to_string(),
+                    pid,
+                    running: process.is_some(),
+                    memory_bytes: process.map(|p| p.memory()),
+                    cpu_percent: process.map(|p| p.cpu_usage()),
+                }
+            })
+            .collect()
+    })
+    .await
+}
+
+// Helper function to get a process's full command line
+fn process_command_line(pid: u32) -> Option<String> {
+    let output = if cfg!(windows) {
+        Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-Command",
+                &format!("(Get-CimInstance Win32_Process -Filter \"ProcessId={}\").CommandLine", pid),
+            ])
+            .output()
+            .ok()?
+    } else {
+        Command::new("ps")
+            .args(["-o", "command=", "-p", &pid.to_string()])
+            .output()
+            .ok()?
+    };
+    let command_line = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!command_line.is_empty()).then_some(command_line)
+}
+
+// Helper function to get a process's executable name
+fn process_name(pid: u32) -> Option<String> {
+    let name = if cfg!(windows) {
+        // `"python.exe","1234","Console","1","12,345 K"`
+        let output = Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+            .output()
+            .ok()?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let first_field = stdout.lines().next()?.split(',').next()?;
+        first_field.trim_matches('"').to_string()
+    } else {
+        let output = Command::new("ps")
+            .args(["-o", "comm=", "-p", &pid.to_string()])
+            .output()
+            .ok()?;
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    // tasklist reports "INFO: No tasks are running..." for an unknown PID
+    (!name.is_empty() && !name.starts_with("INFO:")).then_some(name)
+}
+
+// Helper function to forcibly stop a process we hold no Child handle for
+fn kill_pid(pid: u32) -> Result<(), AppError> {
+    #[cfg(unix)]
+    {
+        unsafe {
+            libc::kill(pid as libc::pid_t, libc::SIGTERM);
+        }
+        let deadline = std::time::Instant::now() + SHUTDOWN_GRACE_PERIOD;
+        while std::time::Instant::now() < deadline {
+            if !process_alive(pid) {
+                return Ok(());
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        if unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) } != 0 {
+            return Err(AppError::io(
+                format!("Failed to kill PID {}", pid),
+                std::io::Error::last_os_error(),
+            ));
+        }
+        Ok(())
+    }
+    #[cfg(not(unix))]
+    {
+        let output = Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/F"])
+            .output()
+            .map_err(|e| AppError::spawn("taskkill", e))?;
+        if !output.status.success() {
+            return Err(AppError::CommandFailed {
+                command: "taskkill".to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+// Report which of the given ports are taken, and by what, for the troubleshooting panel
+#[tauri::command]
+async fn get_ports_in_use(ports: Vec<u16>) -> Result<Vec<PortInfo>, AppError> {
+    // lsof/ss/netstat per port; keep them off the async runtime
+    run_blocking(move || {
+        ports
+            .into_iter()
+            .map(|port| {
+                if port_available(port) {
+                    return PortInfo {
+                        port,
+                        occupied: false,
+                        pid: None,
+                        process_name: None,
+                    };
+                }
+                let pid = find_listening_pid(port);
+                PortInfo {
+                    port,
+                    occupied: true,
+                    pid,
+                    process_name: pid.and_then(process_name),
+                }
+            })
+            .collect()
+    })
+    .await
+}
+
+#[tauri::command]
+async fn kill_orphaned_server(port: u16, confirm: bool) -> Result<String, AppError> {
+    let (pid, command_line) = run_blocking(move || {
+        let pid = find_listening_pid(port)
+            .ok_or_else(|| AppError::not_found(format!("Process listening on port {}", port)))?;
+        Ok::<_, AppError>((pid, process_command_line(pid).unwrap_or_default()))
+    })
+    .await??;
+
+    // Never kill something that doesn't look like one of our servers
+    let lowered = command_line.to_lowercase();
+    let is_ours = OWN_PROCESS_MARKERS
+        .iter()
+        .any(|(program, script)| lowered.contains(program) && lowered.contains(script));
+    if !is_ours {
+        return Err(AppError::InvalidArgument {
+            reason: format!(
+                "PID {} on port {} is not a wirecraft server ({}); refusing to kill it",
+                pid, port, command_line
+            ),
+        });
+    }
+
+    if !confirm {
+        return Err(AppError::InvalidArgument {
+            reason: format!(
+                "Pass confirm to kill PID {} on port {} ({})",
+                pid, port, command_line
+            ),
+        });
+    }
+
+    run_blocking(move || kill_pid(pid)).await??;
+    log::info!("Killed orphaned server PID {} on port {}: {}", pid, port, command_line);
+    Ok(format!("Killed orphaned server (PID {}) on port {}", pid, port))
+}
+
+// Helper function to check whether `pid` is `leader` or in its process group, which is how
+// uvicorn's reload workers relate to the FastAPI process we spawned
+fn in_process_group(pid: u32, leader: u32) -> bool {
+    if pid == leader {
+        return true;
+    }
+    #[cfg(unix)]
+    {
+        unsafe { libc::getpgid(pid as libc::pid_t) == leader as libc::pid_t }
+    }
+    #[cfg(not(unix))]
+    {
+        false
+    }
+}
+
+// Free a port by stopping the process listening on it, but only if this app started it:
+// our MCP/socket servers, our FastAPI server, or the server recorded in the MCP lockfile.
+// Anything else fails with ForeignProcess and is left running.
+#[tauri::command]
+async fn free_our_port(app_handle: tauri::AppHandle, port: u16) -> Result<String, AppError> {
+    let Some(pid) = run_blocking(move || find_listening_pid(port)).await? else {
+        if port_available(port) {
+            return Ok(format!("Port {} is already free", port));
+        }
+        return Err(AppError::not_found(format!("Process listening on port {}", port)));
+    };
+
+    let mcp_pids: Vec<u32> = {
+        let mcp_process = app_handle.state::<McpProcess>();
+        let mut guard = mcp_process.lock().unwrap();
+        let processes = &mut *guard;
+        [processes.server.as_mut(), processes.socket.as_mut()]
+            .into_iter()
+            .flatten()
+            .filter_map(|child| matches!(child.try_wait(), Ok(None)).then(|| child.id()))
+            .collect()
+    };
+    let fastapi_pid = {
+        let fastapi_process = app_handle.state::<FastAPIProcess>();
+        let mut process = fastapi_process.lock().unwrap();
+        process
+            .as_mut()
+            .and_then(|child| matches!(child.try_wait(), Ok(None)).then(|| child.id()))
+    };
+
+    if mcp_pids.contains(&pid) {
+        let stopped = stop_mcp_processes_async(&app_handle).await?;
+        log::info!("Freed port {} by stopping MCP processes {:?}", port, stopped);
+        return Ok(format!("Stopped the MCP server (PID {}) on port {}", pid, port));
+    }
+    if fastapi_pid.is_some_and(|leader| in_process_group(pid, leader)) {
+        stop_fastapi_server(app_handle).await?;
+        log::info!("Freed port {} by stopping the FastAPI server", port);
+        return Ok(format!("Stopped the FastAPI server (PID {}) on port {}", pid, port));
+    }
+    // Left behind by an earlier run of the app, so there is no Child to stop gracefully
+    if read_mcp_lock() == Some(pid) {
+        run_blocking(move || kill_pid(pid)).await??;
+        release_mcp_lock(pid);
+        log::info!("Freed port {} by stopping MCP server PID {} from the lockfile", port, pid);
+        return Ok(format!("Stopped the previous MCP server (PID {}) on port {}", pid, port));
+    }
+
+    let process = run_blocking(move || process_name(pid)).await?;
+    Err(AppError::ForeignProcess {
+        port,
+        pid,
+        process: process.unwrap_or_else(|| "an unknown process".to_string()),
+    })
+}
+
+// Crash Supervisor
+
+fn set_desired_running(app_handle: &tauri::AppHandle, server: ManagedServer, running: bool) {
+    let supervisor = app_handle.state::<Supervisor>();
+    let mut supervisor = supervisor.lock().unwrap();
+    let state = supervisor.server_mut(server);
+    state.desired_running = running;
+    // A fresh start (or a stop) leaves nothing running on stale settings
+    state.config_dirty = false;
+    state.started_at = running.then(std::time::SystemTime::now);
+}
+
+// Clears RestartState::restarting when a config-driven restart ends, however it ends
+struct RestartingGuard(tauri::AppHandle, ManagedServer);
+
+impl Drop for RestartingGuard {
+    fn drop(&mut self) {
+        let supervisor = self.0.state::<Supervisor>();
+        supervisor.lock().unwrap().server_mut(self.1).restarting = false;
+    }
+}
+
+// Helper function to check whether we have a live process for a server
+fn server_running(app_handle: &tauri::AppHandle, server: ManagedServer) -> bool {
+    match server {
+        ManagedServer::Mcp => app_handle.state::<McpProcess>().lock().unwrap().server.is_some(),
+        ManagedServer::FastApi => app_handle.state::<FastAPIProcess>().lock().unwrap().is_some(),
+    }
+}
+
+// Helper function to restart a running server so it picks up the current configuration
+async fn restart_with_new_config(
+    app_handle: &tauri::AppHandle,
+    server: ManagedServer,
+) -> Result<String, AppError> {
+    app_handle.state::<Supervisor>().lock().unwrap().server_mut(server).restarting = true;
+    let _guard = RestartingGuard(app_handle.clone(), server);
+
+    match server {
+        ManagedServer::Mcp => {
+            let install_dir = app_handle.state::<McpProcess>().lock().unwrap().install_dir.clone();
+            stop_mcp_processes_async(app_handle).await?;
+            start_mcp_server(app_handle.clone(), install_dir).await
+        }
+        ManagedServer::FastApi => {
+            stop_fastapi_server(app_handle.clone()).await?;
+            start_fastapi_server(app_handle.clone(), None).await
+        }
+    }
+}
+
+// 1s, 2s, 4s, ... capped at MAX_RESTART_BACKOFF
+fn restart_backoff(attempt: u32) -> std::time::Duration {
+    std::time::Duration::from_secs(1u64 << attempt.min(6)).min(MAX_RESTART_BACKOFF)
+}
+
+// Helper function to take ownership of a server's process(es) once they have exited.
+// Returns the exit code (if any) when an exit was detected.
+async fn reap_exited_server(
+    app_handle: &tauri::AppHandle,
+    server: ManagedServer,
+) -> Option<Option<i32>> {
+    match server {
+        ManagedServer::FastApi => {
+            let fastapi_process = app_handle.state::<FastAPIProcess>();
+            let mut process = fastapi_process.lock().unwrap();
+            let status = process.as_mut()?.try_wait().ok()??;
+            if let Some(child) = process.take() {
+                // Don't let orphaned reload workers hold the port against the restart
+                kill_process_group(child.id());
+            }
+            Some(status.code())
+        }
+        ManagedServer::Mcp => {
+            let (exited, children) = {
+                let mcp_process = app_handle.state::<McpProcess>();
+                let mut guard = mcp_process.lock().unwrap();
+                let processes = &mut *guard;
+                let exited = [processes.server.as_mut(), processes.socket.as_mut()]
+                    .into_iter()
+                    .flatten()
+                    .find_map(|child| child.try_wait().ok().flatten())?;
+
+                // The MCP server and socket server are restarted as a pair
+                let children: Vec<Child> = [processes.server.take(), processes.socket.take()]
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                (exited, children)
+            };
+
+            // Waiting out the grace period must not hold the lock or a runtime worker
+            let _ = run_blocking(move || {
+                for mut child in children {
+                    let _ = terminate_child(&mut child, SHUTDOWN_GRACE_PERIOD);
+                }
+            })
+            .await;
+            Some(exited.code())
+        }
+    }
+}
+
+// Poll a managed server and restart it with exponential backoff if it crashes
+async fn supervise_server(app_handle: tauri::AppHandle, server: ManagedServer) {
+    let supervisor: Supervisor = app_handle.state::<Supervisor>().inner().clone();
+
+    loop {
+        tokio::time::sleep(SUPERVISOR_POLL_INTERVAL).await;
+
+        let Some(exit_code) = reap_exited_server(&app_handle, server).await else {
+            // Forget old crashes once the server has stayed up for a while
+            let mut state = supervisor.lock().unwrap();
+            let state = state.server_mut(server);
+            if state
+                .last_restart_at
+                .is_some_and(|at| at.elapsed() > MAX_RESTART_BACKOFF)
+            {
+                state.restart_count = 0;
+                state.last_restart_at = None;
+            }
+            continue;
+        };
+
+        log::warn!("{} exited unexpectedly (exit code {:?})", server.name(), exit_code);
+        {
+            let mut state = supervisor.lock().unwrap();
+            let state = state.server_mut(server);
+            state.last_exit_code = exit_code;
+            state.last_error = stderr_tail(&app_handle, server.name());
+        }
+
+        loop {
+            let attempt = {
+                let mut state = supervisor.lock().unwrap();
+                let state = state.server_mut(server);
+                if !state.desired_running || state.restarting {
+                    break;
+                }
+                state.restart_count += 1;
+                state.last_restart_at = Some(std::time::Instant::now());
+                state.restart_count
+            };
+
+            let max_retries = server_config(&app_handle).max_restart_retries;
+            if attempt > max_retries {
+                log::error!("Giving up on restarting {} after {} attempts", server.name(), max_retries);
+                let _ = app_handle.emit(
+                    "process://restart-failed",
+                    ServerRestartEvent {
+                        server: server.name().to_string(),
+                        attempt,
+                        exit_code,
+                    },
+                );
+                break;
+            }
+
+            tokio::time::sleep(restart_backoff(attempt - 1)).await;
+            {
+                let mut state = supervisor.lock().unwrap();
+                let state = state.server_mut(server);
+                if !state.desired_running || state.restarting {
+                    break;
+                }
+            }
+
+            let result = match server {
+                ManagedServer::Mcp => {
+                    let install_dir = app_handle.state::<McpProcess>().lock().unwrap().install_dir.clone();
+                    start_mcp_server(app_handle.clone(), install_dir).await
+                }
+                ManagedServer::FastApi => start_fastapi_server(app_handle.clone(), None).await,
+            };
+            match result {
+                Ok(msg) => {
+                    log::info!("Restarted {} (attempt {}): {}", server.name(), attempt, msg);
+                    let _ = app_handle.emit(
+                        "process://restarted",
+                        ServerRestartEvent {
+                            server: server.name().to_string(),
+                            attempt,
+                            exit_code,
+                        },
+                    );
+                    break;
+                }
+                Err(e) => log::error!("Failed to restart {} (attempt {}): {}", server.name(), attempt, e),
+            }
+        }
+    }
+}
+
+// Watch a running server for hangs: it is up, but failing health (FastAPI) or readiness
+// (MCP) checks. Crashes are left to supervise_server.
+async fn watch_server_health(app_handle: tauri::AppHandle, server: ManagedServer) {
+    let supervisor: Supervisor = app_handle.state::<Supervisor>().inner().clone();
+
+    loop {
+        let config = server_config(&app_handle);
+        let interval = std::time::Duration::from_secs(config.health_check_interval_secs.max(1));
+        tokio::time::sleep(interval).await;
+
+        // Servers still inside their startup window are the readiness checks' business
+        let startup_grace = match server {
+            ManagedServer::Mcp => MCP_READY_TIMEOUT,
+            ManagedServer::FastApi => FASTAPI_READY_TIMEOUT,
+        };
+        let watching = {
+            let mut state = supervisor.lock().unwrap();
+            let state = state.server_mut(server);
+            let settled = state
+                .started_at
+                .and_then(|at| at.elapsed().ok())
+                .is_some_and(|uptime| uptime >= startup_grace);
+            let watching = state.desired_running && !state.restarting && settled;
+            if !watching {
+                state.health_failures = 0;
+                state.unhealthy = false;
+            }
+            watching
+        };
+        if !watching || !server_running(&app_handle, server) {
+            continue;
+        }
+
+        let result = match server {
+            ManagedServer::Mcp => {
+                if probe_socket_server(config.socket_port).await {
+                    Ok(())
+                } else {
+                    Err(format!("socket server on port {} is not responding", config.socket_port))
+                }
+            }
+            ManagedServer::FastApi => check_fastapi_health(app_handle.clone())
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+        };
+
+        let event = {
+            let mut state = supervisor.lock().unwrap();
+            let state = state.server_mut(server);
+            match result {
+                Ok(()) => {
+                    let recovered = state.unhealthy;
+                    let failures = std::mem::take(&mut state.health_failures);
+                    state.unhealthy = false;
+                    recovered.then_some(("server-recovered", failures, None))
+                }
+                Err(error) => {
+                    state.health_failures += 1;
+                    let newly_unhealthy =
+                        !state.unhealthy && state.health_failures >= config.unhealthy_threshold.max(1);
+                    state.unhealthy |= newly_unhealthy;
+                    newly_unhealthy.then_some(("server-unhealthy", state.health_failures, Some(error)))
+                }
+            }
+        };
+
+        if let Some((name, consecutive_failures, error)) = event {
+            match &error {
+                Some(error) => log::warn!(
+                    "{} failed {} consecutive health checks: {}",
+                    server.name(),
+                    consecutive_failures,
+                    error
+                ),
+                None => log::info!("{} is healthy again", server.name()),
+            }
+            let _ = app_handle.emit(
+                name,
+                ServerHealthEvent {
+                    server: server.name().to_string(),
+                    consecutive_failures,
+                    error,
+                },
+            );
+        }
+    }
+}
+
+// FastAPI Server Management Functions
+
+#[tauri::command]
+async fn start_fastapi_server(
+    app_handle: tauri::AppHandle,
+    force_reinstall: Option<bool>,
+) -> Result<String, AppError> {
+    let fastapi_process: FastAPIProcess = app_handle.state::<FastAPIProcess>().inner().clone();
+    
+    // Check if already running
+    {
+        let mut process = fastapi_process.lock().unwrap();
+        if let Some(child) = process.as_mut() {
+            if let Ok(None) = child.try_wait() {
+                return Ok("FastAPI server is already running".to_string());
+            }
+        }
+    }
+
+    let fastapi_port = server_config(&app_handle).fastapi_port;
+    if !port_available(fastapi_port) {
+        return Err(AppError::PortInUse { port: fastapi_port });
+    }
+
+    // Find the FastAPI directory - try multiple locations
+    let fastapi_dir = resolve_fastapi_dir(&app_handle);
+
+    let fastapi_dir = fastapi_dir.ok_or_else(|| {
+        AppError::not_found("FastAPI directory (expected at resource/mcp-client-python/api)")
+    })?;
+    log::info!("Using FastAPI directory: {:?}", fastapi_dir);
+    record_resolution(&app_handle, |resolution| {
+        resolution.fastapi_dir = Some(fastapi_dir.display().to_string());
+        resolution.python_path = None;
+        resolution.venv_python = None;
+    });
+
+    // Check if requirements.txt exists
+    let requirements_file = fastapi_dir.join("requirements.txt");
+    if !requirements_file.exists() {
+        return Err(AppError::not_found("requirements.txt in FastAPI directory"));
+    }
+
+    let force_reinstall = force_reinstall.unwrap_or(false);
+    let venv = prepare_fastapi_venv(&app_handle, &fastapi_dir, force_reinstall).await?;
+    record_resolution(&app_handle, |resolution| {
+        resolution.python_path = Some(venv.base_python.clone());
+        resolution.venv_python = Some(venv.python.display().to_string());
+    });
+
+    // Check if main.py exists
+    let main_py = fastapi_dir.join("main.py");
+    if !main_py.exists() {
+        return Err(AppError::not_found("main.py in FastAPI directory"));
+    }
+
+    // The venv work above is independent of MCP, so only the launch itself waits
+    if server_config(&app_handle).fastapi_depends_on_mcp {
+        emit_startup_phase(&app_handle, ManagedServer::FastApi, StartupPhase::WaitingForMcp);
+        wait_for_mcp_status_ready(&app_handle).await?;
+    }
+    emit_startup_phase(&app_handle, ManagedServer::FastApi, StartupPhase::Starting);
+
+    // Start the FastAPI server; main.py hands FASTAPI_RELOAD to uvicorn's reload flag
+    let config = server_config(&app_handle);
+    let mode = config.mode;
+    log::info!(
+        "Starting FastAPI server on {}:{} ({:?})...",
+        config.fastapi_host,
+        fastapi_port,
+        mode
+    );
+    if config.fastapi_host_exposed() {
+        log::warn!(
+            "FastAPI binds {}, so it is reachable from other machines",
+            config.fastapi_host
+        );
+    }
+    *app_handle.state::<FastAPIBoundPort>().lock().unwrap() = None;
+    if !config.env.is_empty() {
+        log::info!("FastAPI environment overrides: {:?}", redact_env(&config.env));
+    }
+    let mut command = Command::new(&venv.python);
+    command
         .arg("main.py")
+        .args(&config.fastapi_args)
         .current_dir(&fastapi_dir)
+        .envs(&config.env)
+        .env("PORT", fastapi_port.to_string())
+        .env("FASTAPI_PORT", fastapi_port.to_string())
+        .env("FASTAPI_HOST", &config.fastapi_host)
+        .env("FASTAPI_RELOAD", (mode == ServerMode::Development).to_string())
+        .env("ENV", mode.env_value())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // Its own process group, so stopping it also reaches uvicorn's reload workers
+    configure_process_group(&mut command);
+    let mut child = command
         .spawn()
-        .map_err(|e| format!("Failed to start FastAPI server: {}", e))?;
+        .map_err(|e| AppError::spawn("FastAPI server", e))?;
+    capture_child_output(&app_handle, &mut child, "fastapi");
+
+    let pid = child.id();
+    log::info!("FastAPI server started with PID: {}", pid);
+    
+    // Store the process
+    {
+        let mut process = fastapi_process.lock().unwrap();
+        *process = Some(child);
+    }
+    check_fastapi_startup(&app_handle, pid).await?;
+    set_desired_running(&app_handle, ManagedServer::FastApi, true);
+    tauri::async_runtime::spawn(wait_for_fastapi_ready(app_handle.clone(), pid, fastapi_port));
+
+    Ok(format!(
+        "FastAPI server started with PID: {} (dependencies {})",
+        pid,
+        if venv.deps_skipped { "skipped" } else { "installed" }
+    ))
+}
+
+// The FastAPI virtualenv after prepare_fastapi_venv
+struct FastApiVenv {
+    dir: std::path::PathBuf,
+    python: std::path::PathBuf,
+    // Interpreter the venv was created from
+    base_python: String,
+    // pip was skipped because requirements haven't changed since the last clean install
+    deps_skipped: bool,
+    // Every pip install succeeded (or was skipped)
+    deps_clean: bool,
+}
+
+// Helper function to create the FastAPI virtualenv if it is missing and install its
+// dependencies into it. Pip failures are logged rather than returned, so the server
+// still gets a chance to start.
+async fn prepare_fastapi_venv(
+    app_handle: &tauri::AppHandle,
+    fastapi_dir: &std::path::Path,
+    force_reinstall: bool,
+) -> Result<FastApiVenv, AppError> {
+    // Get Python path
+    let python_app_handle = app_handle.clone();
+    let python_path = run_blocking(move || get_python_path(&python_app_handle)).await??;
+    log::info!("Using Python: {}", python_path);
+
+    // Create virtual environment if it doesn't exist
+    let venv_dir = fastapi_dir.join("venv");
+    if !venv_dir.exists() {
+        log::info!("Creating Python virtual environment...");
+        let output = tokio::process::Command::new(&python_path)
+            .args(["-m", "venv", "venv"])
+            .current_dir(fastapi_dir)
+            .output()
+            .await
+            .map_err(|e| AppError::spawn("python -m venv", e))?;
+
+        if !output.status.success() {
+            // A half-created venv would be mistaken for a good one on the next start
+            if let Err(e) = std::fs::remove_dir_all(&venv_dir) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    log::warn!("Failed to remove partial virtual environment: {}", e);
+                }
+            }
+
+            let mut stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            // Debian/Ubuntu split ensurepip out of the base Python package
+            if stderr.contains("ensurepip") {
+                stderr.push_str(
+                    "\nHint: install the python3-venv package (e.g. `sudo apt install python3-venv`) and try again.",
+                );
+            }
+            return Err(AppError::CommandFailed {
+                command: "python -m venv".to_string(),
+                stderr,
+            });
+        }
+        log::info!("Virtual environment created successfully");
+    } else {
+        log::info!("Virtual environment already exists");
+    }
+
+    // Get the Python executable from the virtual environment
+    let venv_python = if cfg!(windows) {
+        venv_dir.join("Scripts/python.exe")
+    } else {
+        venv_dir.join("bin/python")
+    };
+
+    if !venv_python.exists() {
+        return Err(AppError::not_found(format!(
+            "Virtual environment Python at {:?}",
+            venv_python
+        )));
+    }
+
+    // Skip pip entirely when requirements haven't changed since the last clean install
+    let parent_dir = fastapi_dir.parent().unwrap();
+    let deps_hash_file = venv_dir.join(".deps-hash");
+    let deps_hash = fastapi_deps_hash(fastapi_dir)
+        .map_err(|e| AppError::io("Failed to hash FastAPI requirements", e))?;
+    let deps_current = !force_reinstall
+        && std::fs::read_to_string(&deps_hash_file)
+            .map(|stored| stored.trim() == deps_hash)
+            .unwrap_or(false);
+
+    let mut deps_clean = true;
+    if deps_current {
+        log::info!("FastAPI dependencies are up to date, skipping pip install");
+    } else {
+        // Offline installs resolve everything from the pre-staged wheelhouse
+        let config = server_config(app_handle);
+        let mut offline_args = Vec::new();
+        if config.offline() {
+            let wheelhouse = config.wheelhouse_dir()?;
+            if !wheelhouse.is_dir() {
+                return Err(AppError::not_found(format!(
+                    "Offline wheelhouse at {}",
+                    wheelhouse.display()
+                )));
+            }
+            offline_args.push("--no-index".to_string());
+            offline_args.push("--find-links".to_string());
+            offline_args.push(wheelhouse.to_string_lossy().to_string());
+        }
+
+        // Install dependencies
+        let cancel = begin_operation(app_handle);
+        log::info!("Installing FastAPI dependencies...");
+        let mut pip_command = tokio::process::Command::new(&venv_python);
+        pip_command
+            .args(["-m", "pip", "install", "-r", "requirements.txt"])
+            .args(&offline_args)
+            .current_dir(fastapi_dir);
+        let pip_install = run_cancellable(pip_command, &cancel)
+            .await?
+            .map_err(|e| AppError::spawn("pip install", e))?;
+
+        let mut deps_installed = pip_install.status.success();
+        if !deps_installed {
+            log::warn!("Pip install had issues: {}", String::from_utf8_lossy(&pip_install.stderr));
+            // Don't fail here, continue to try starting the server
+        } else {
+            log::info!("Dependencies installed successfully");
+        }
+
+        // Install the parent package if pyproject.toml exists
+        if parent_dir.join("pyproject.toml").exists() {
+            log::info!("Installing parent package...");
+            let mut parent_command = tokio::process::Command::new(&venv_python);
+            parent_command
+                .args(["-m", "pip", "install", "-e", "."])
+                .args(&offline_args)
+                .current_dir(parent_dir);
+            let parent_install = run_cancellable(parent_command, &cancel).await?;
+
+            match parent_install {
+                Ok(output) => {
+                    if output.status.success() {
+                        log::info!("Parent package installed successfully");
+                    } else {
+                        deps_installed = false;
+                        log::warn!("Parent package install had issues: {}", String::from_utf8_lossy(&output.stderr));
+                    }
+                }
+                Err(e) => {
+                    deps_installed = false;
+                    log::warn!("Failed to install parent package: {}", e);
+                }
+            }
+        }
+
+        // Only cache a clean install so a failed one is retried next start
+        if deps_installed {
+            if let Err(e) = std::fs::write(&deps_hash_file, &deps_hash) {
+                log::warn!("Failed to record FastAPI dependency hash: {}", e);
+            }
+        }
+        deps_clean = deps_installed;
+    }
+
+    Ok(FastApiVenv {
+        dir: venv_dir,
+        python: venv_python,
+        base_python: python_path,
+        deps_skipped: deps_current,
+        deps_clean,
+    })
+}
+
+// Delete the FastAPI virtualenv and build it again from scratch, for venvs corrupted
+// badly enough that pip errors out. FastAPI is stopped first and restarted afterwards.
+#[tauri::command]
+async fn rebuild_fastapi_venv(
+    app_handle: tauri::AppHandle,
+) -> Result<VenvRebuildSummary, AppError> {
+    let fastapi_dir = resolve_fastapi_dir(&app_handle).ok_or_else(|| {
+        AppError::not_found("FastAPI directory (expected at resource/mcp-client-python/api)")
+    })?;
+    let venv_dir = fastapi_dir.join("venv");
+    // Only ever delete something python -m venv created
+    if venv_dir.exists() && !venv_dir.join("pyvenv.cfg").is_file() {
+        return Err(AppError::InvalidArgument {
+            reason: format!("{} has no pyvenv.cfg; refusing to delete it", venv_dir.display()),
+        });
+    }
+
+    let was_running = server_running(&app_handle, ManagedServer::FastApi);
+    if was_running {
+        let msg = stop_fastapi_server(app_handle.clone()).await?;
+        log::info!("Stopped FastAPI before rebuilding its venv: {}", msg);
+    }
+
+    if venv_dir.exists() {
+        log::info!("Removing FastAPI virtual environment {:?}", venv_dir);
+        let dir = venv_dir.clone();
+        run_blocking(move || std::fs::remove_dir_all(&dir))
+            .await?
+            .map_err(|e| AppError::io(format!("Failed to remove {:?}", venv_dir), e))?;
+    }
+
+    let venv = prepare_fastapi_venv(&app_handle, &fastapi_dir, true).await?;
+    if was_running {
+        start_fastapi_server(app_handle.clone(), None).await?;
+    }
+
+    Ok(VenvRebuildSummary {
+        venv_dir: venv.dir.to_string_lossy().to_string(),
+        python: venv.python.to_string_lossy().to_string(),
+        dependencies_installed: venv.deps_clean,
+        restarted: was_running,
+    })
+}
+
+// Helper function to catch a FastAPI process that dies right after spawning, so the
+// caller gets the traceback instead of a PID. Returns early once uvicorn reports its port.
+async fn check_fastapi_startup(app_handle: &tauri::AppHandle, pid: u32) -> Result<(), AppError> {
+    let deadline = std::time::Instant::now() + FASTAPI_STARTUP_CHECK;
+    while std::time::Instant::now() < deadline {
+        if app_handle.state::<FastAPIBoundPort>().lock().unwrap().is_some() {
+            return Ok(());
+        }
+
+        let exited = {
+            let fastapi_process = app_handle.state::<FastAPIProcess>();
+            let mut process = fastapi_process.lock().unwrap();
+            match process.as_mut() {
+                // Stopped or replaced while starting up; nothing left to check
+                Some(child) if child.id() != pid => return Ok(()),
+                None => return Ok(()),
+                Some(child) => match child.try_wait() {
+                    Ok(Some(status)) => {
+                        *process = None;
+                        Some(status)
+                    }
+                    _ => None,
+                },
+            }
+        };
+
+        if let Some(status) = exited {
+            // Let the log readers drain the last of stderr before reading the tail
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            log::error!("FastAPI server exited during startup ({})", status);
+            return Err(AppError::StartupFailed {
+                server: "FastAPI server".to_string(),
+                exit_code: status.code(),
+                stderr: stderr_tail(app_handle, ManagedServer::FastApi.name()).unwrap_or_default(),
+            });
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    Ok(())
+}
+
+// Helper function to hash the files that determine the FastAPI venv's contents
+fn fastapi_deps_hash(fastapi_dir: &std::path::Path) -> std::io::Result<String> {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update(std::fs::read(fastapi_dir.join("requirements.txt"))?);
+    if let Some(parent_dir) = fastapi_dir.parent() {
+        let pyproject = parent_dir.join("pyproject.toml");
+        if pyproject.exists() {
+            hasher.update(std::fs::read(pyproject)?);
+        }
+    }
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+#[tauri::command]
+async fn stop_fastapi_server(app_handle: tauri::AppHandle) -> Result<String, AppError> {
+    let fastapi_process: FastAPIProcess = app_handle.state::<FastAPIProcess>().inner().clone();
+    set_desired_running(&app_handle, ManagedServer::FastApi, false);
+    let grace_period =
+        std::time::Duration::from_secs(server_config(&app_handle).fastapi_shutdown_grace_secs);
+    
+    // Take the child out so the lock isn't held while waiting for it to exit
+    let child = fastapi_process.lock().unwrap().take();
+    if let Some(mut child) = child {
+        let stopped = run_blocking(move || terminate_process_group(&mut child, grace_period)).await?;
+        match stopped {
+            Ok(true) => Ok("FastAPI server stopped gracefully".to_string()),
+            Ok(false) => Ok(format!(
+                "FastAPI server did not exit within {}s and was force-killed",
+                grace_period.as_secs()
+            )),
+            Err(e) => Err(AppError::io("Failed to stop FastAPI server", e))
+        }
+    } else {
+        Ok("FastAPI server is not running".to_string())
+    }
+}
+
+// Shut everything down: FastAPI first, since it may depend on MCP, then the MCP and
+// socket servers. Services that weren't running count as stopped.
+#[tauri::command]
+async fn stop_all(app_handle: tauri::AppHandle) -> Result<StopAllReport, AppError> {
+    let mut report = StopAllReport::default();
+
+    report
+        .run("stop_fastapi_server", stop_fastapi_server(app_handle.clone()))
+        .await;
+    report
+        .run("stop_mcp_server", async {
+            let stopped = stop_mcp_processes_async(&app_handle).await?;
+            Ok(if stopped.is_empty() {
+                "MCP server is not running".to_string()
+            } else {
+                format!("Stopped MCP processes {:?}", stopped)
+            })
+        })
+        .await;
+
+    report.success = !report
+        .steps
+        .iter()
+        .any(|step| matches!(step.status, StepStatus::Failed));
+    Ok(report)
+}
+
+#[tauri::command]
+async fn get_fastapi_server_status(app_handle: tauri::AppHandle) -> Result<FastAPIStatus, AppError> {
+    let fastapi_process: FastAPIProcess = app_handle.state::<FastAPIProcess>().inner().clone();
+    let config = server_config(&app_handle);
+    let (restart_count, last_exit_code, last_error, config_dirty, (started_at, uptime_secs)) = {
+        let supervisor = app_handle.state::<Supervisor>();
+        let supervisor = supervisor.lock().unwrap();
+        (
+            supervisor.fastapi.restart_count,
+            supervisor.fastapi.last_exit_code,
+            supervisor.fastapi.last_error.clone(),
+            supervisor.fastapi.config_dirty,
+            supervisor.fastapi.uptime(),
+        )
+    };
+    
+    let fastapi_port = current_fastapi_port(&app_handle);
+    let mut process = fastapi_process.lock().unwrap();
+    if let Some(child) = process.as_mut() {
+        match child.try_wait() {
+            Ok(None) => {
+                // Process is still running
+                Ok(FastAPIStatus {
+                    running: true,
+                    port: Some(fastapi_port),
+                    pid: Some(child.id()),
+                    health_check_url: Some(config.fastapi_health_url(fastapi_port)),
+                    restart_count,
+                    last_exit_code,
+                    last_error,
+                    mode: config.mode,
+                    config_dirty,
+                    started_at,
+                    uptime_secs,
+                    exposure_warning: config.fastapi_host_exposed().then(|| {
+                        format!(
+                            "Bound to {}; the API is reachable from other machines on the network",
+                            config.fastapi_host
+                        )
+                    }),
+                    host: config.fastapi_host,
+                })
+            }
+            Ok(Some(status)) => {
+                // Process has exited; leave the handle for the supervisor to reap
+                Ok(FastAPIStatus {
+                    running: false,
+                    port: None,
+                    pid: None,
+                    health_check_url: None,
+                    restart_count,
+                    last_exit_code: status.code().or(last_exit_code),
+                    last_error: stderr_tail(&app_handle, ManagedServer::FastApi.name()).or(last_error),
+                    mode: config.mode,
+                    config_dirty: false,
+                    started_at: None,
+                    uptime_secs: None,
+                    host: config.fastapi_host,
+                    exposure_warning: None,
+                })
+            }
+            Err(e) => Err(AppError::io("Failed to check process status", e))
+        }
+    } else {
+        Ok(FastAPIStatus {
+            running: false,
+            port: None,
+            pid: None,
+            health_check_url: None,
+            restart_count,
+            last_exit_code,
+            last_error,
+            mode: config.mode,
+            config_dirty: false,
+            started_at: None,
+            uptime_secs: None,
+            host: config.fastapi_host,
+            exposure_warning: None,
+        })
+    }
+}
+
+fn emit_startup_phase(app_handle: &tauri::AppHandle, server: ManagedServer, phase: StartupPhase) {
+    let event = StartupPhaseEvent {
+        server: server.name().to_string(),
+        phase,
+    };
+    if let Err(e) = app_handle.emit("startup-phase", event) {
+        log::warn!("Failed to emit startup phase: {}", e);
+    }
+}
+
+// Helper function to wait for get_mcp_server_status to report the MCP server ready
+async fn wait_for_mcp_status_ready(app_handle: &tauri::AppHandle) -> Result<(), AppError> {
+    let deadline = std::time::Instant::now() + MCP_DEPENDENCY_TIMEOUT;
+    while std::time::Instant::now() < deadline {
+        if get_mcp_server_status(app_handle.clone()).await?.running {
+            return Ok(());
+        }
+        tokio::time::sleep(FASTAPI_READY_POLL_INTERVAL).await;
+    }
+    Err(AppError::Timeout {
+        operation: "Waiting for the MCP server before starting FastAPI".to_string(),
+        seconds: MCP_DEPENDENCY_TIMEOUT.as_secs(),
+        stderr: String::new(),
+    })
+}
+
+// Poll the health endpoint after a start and tell the frontend once the server is usable
+async fn wait_for_fastapi_ready(app_handle: tauri::AppHandle, pid: u32, port: u16) {
+    let deadline = std::time::Instant::now() + FASTAPI_READY_TIMEOUT;
+    let mut last_error = String::from("server did not respond");
+
+    while std::time::Instant::now() < deadline {
+        tokio::time::sleep(FASTAPI_READY_POLL_INTERVAL).await;
+
+        // Give up quietly if this process was stopped or replaced; the supervisor reports crashes
+        let still_current = app_handle
+            .state::<FastAPIProcess>()
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|child| child.id() == pid);
+        if !still_current {
+            return;
+        }
+
+        match check_fastapi_health(app_handle.clone()).await {
+            Ok(_) => {
+                // With an ephemeral port, the real one is known by the time health passes
+                let port = current_fastapi_port(&app_handle);
+                log::info!("FastAPI server ready on port {}", port);
+                let _ = app_handle.emit(
+                    "fastapi-ready",
+                    FastAPIReadinessEvent {
+                        port,
+                        pid,
+                        error: None,
+                    },
+                );
+                return;
+            }
+            Err(e) => last_error = e.to_string(),
+        }
+    }
+
+    log::error!(
+        "FastAPI server did not become healthy within {}s: {}",
+        FASTAPI_READY_TIMEOUT.as_secs(),
+        last_error
+    );
+    let _ = app_handle.emit(
+        "fastapi-failed",
+        FastAPIReadinessEvent {
+            port,
+            pid,
+            error: Some(last_error),
+        },
+    );
+}
+
+const SOCKET_LINK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+// Connect to the socket server as a WebSocket client, the way the FastAPI app's MCP client
+// does, and time a channel join round-trip. Failures are reported in the result, not as errors.
+#[tauri::command]
+async fn check_mcp_socket_link(app_handle: tauri::AppHandle) -> Result<SocketLinkCheck, AppError> {
+    let port = server_config(&app_handle).socket_port;
+    let started = std::time::Instant::now();
+
+    let result = tokio::time::timeout(SOCKET_LINK_TIMEOUT, socket_join_round_trip(port)).await;
+    let error = match result {
+        Ok(Ok(())) => None,
+        Ok(Err(e)) => Some(e),
+        Err(_) => Some(format!("No reply within {}s", SOCKET_LINK_TIMEOUT.as_secs())),
+    };
+
+    Ok(SocketLinkCheck {
+        port,
+        success: error.is_none(),
+        latency_ms: error.is_none().then(|| started.elapsed().as_millis() as u64),
+        error,
+    })
+}
+
+// Helper function to join a throwaway channel and wait for the server to acknowledge it
+async fn socket_join_round_trip(port: u16) -> Result<(), String> {
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::Message;
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://127.0.0.1:{}", port))
+        .await
+        .map_err(|e| format!("Failed to connect: {}", e))?;
+
+    let request_id = format!("wirecraft-link-check-{}", std::process::id());
+    let join = serde_json::json!({
+        "type": "join",
+        "channel": request_id,
+        "id": request_id,
+    });
+    socket
+        .send(Message::Text(join.to_string()))
+        .await
+        .map_err(|e| format!("Failed to send join: {}", e))?;
+
+    // Skip the welcome banner and join notice until our request id is echoed back
+    while let Some(message) = socket.next().await {
+        let message = message.map_err(|e| format!("Connection failed: {}", e))?;
+        let Message::Text(text) = message else {
+            continue;
+        };
+        let Ok(reply) = serde_json::from_str::<serde_json::Value>(&text) else {
+            continue;
+        };
+        if reply["type"] == "error" {
+            return Err(format!("Socket server rejected the join: {}", reply["message"]));
+        }
+        if reply["message"]["id"] == request_id.as_str() {
+            let _ = socket.close(None).await;
+            return Ok(());
+        }
+    }
+    Err("Socket server closed the connection".to_string())
+}
+
+// GET FastAPI's /health and report its status. A non-2xx response or a body other than
+// `{"status": "healthy", ...}` fails with HealthCheckFailed.
+#[tauri::command]
+async fn check_fastapi_health(app_handle: tauri::AppHandle) -> Result<HealthReport, AppError> {
+    let health_check_url =
+        server_config(&app_handle).fastapi_health_url(current_fastapi_port(&app_handle));
+
+    let started = std::time::Instant::now();
+    let details = process::probe_health(&health_check_url, std::time::Duration::from_secs(5))
+        .await
+        .map_err(|reason| AppError::HealthCheckFailed { reason })?;
+    Ok(HealthReport {
+        status: details
+            .get("status")
+            .and_then(|status| status.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        latency_ms: started.elapsed().as_millis() as u64,
+        details,
+    })
+}
+
+// Check everything the servers need in one go and explain how to fix what's wrong
+#[tauri::command]
+async fn diagnose(app_handle: tauri::AppHandle) -> Result<Vec<Diagnostic>, AppError> {
+    let (bun, python, install, venv, ports) = tokio::join!(
+        diagnose_bun(),
+        diagnose_python(app_handle.clone()),
+        diagnose_install(app_handle.clone()),
+        diagnose_venv(app_handle.clone()),
+        diagnose_ports(app_handle.clone()),
+    );
+
+    let mut diagnostics = vec![bun?, python?, install?, venv?];
+    diagnostics.extend(ports?);
+    Ok(diagnostics)
+}
+
+async fn diagnose_bun() -> Result<Diagnostic, AppError> {
+    let status = check_bun_installation().await?;
+    let minimum = min_bun();
+    Ok(match (status.path, runtime_version(status.version, minimum.clone())) {
+        (None, _) => Diagnostic::fail("bun", "Bun is not installed", "Run install_bun"),
+        (Some(path), version) if version.compatible => Diagnostic::ok(
+            "bun",
+            format!("Bun {} at {}", version.raw.unwrap_or_default(), path),
+        ),
+        (Some(path), version) => Diagnostic::warn(
+            "bun",
+            format!(
+                "Bun {} at {} is older than the supported {}",
+                version.raw.as_deref().unwrap_or("(unknown version)"),
+                path,
+                minimum
+            ),
+            "Upgrade Bun with `bun upgrade` or run install_bun",
+        ),
+    })
+}
+
+async fn diagnose_python(app_handle: tauri::AppHandle) -> Result<Diagnostic, AppError> {
+    run_blocking(move || match resolve_python(&app_handle) {
+        Ok((path, version)) => Diagnostic::ok("python", format!("Python {} at {}", version, path)),
+        Err(e @ AppError::PythonTooOld { .. }) => Diagnostic::fail(
+            "python",
+            e.to_string(),
+            format!(
+                "Install Python {}.{}+ or point python_path at one",
+                MIN_PYTHON.0, MIN_PYTHON.1
+            ),
+        ),
+        Err(AppError::PythonNotFound) => Diagnostic::fail(
+            "python",
+            "No Python interpreter found on PATH",
+            format!("Install Python {}.{}+", MIN_PYTHON.0, MIN_PYTHON.1),
+        ),
+        Err(e) => {
+            Diagnostic::fail("python", e.to_string(), "Fix or clear python_path in the config")
+        }
+    })
+    .await
+}
+
+async fn diagnose_install(app_handle: tauri::AppHandle) -> Result<Diagnostic, AppError> {
+    let mcp_server_dir = mcp_server_dir(&app_handle, None)?;
+    if !check_mcp_server_installation(app_handle.clone(), None, None).await? {
+        return Ok(Diagnostic::fail(
+            "mcp_install",
+            format!("MCP server is not installed in {}", mcp_server_dir.display()),
+            "Run install_mcp_server",
+        ));
+    }
+    if !mcp_server_dir.join("node_modules").is_dir() {
+        return Ok(Diagnostic::fail(
+            "mcp_install",
+            "MCP server dependencies are missing",
+            "Run reinstall_mcp_dependencies",
+        ));
+    }
+
+    let dir = mcp_server_dir.clone();
+    let verification = run_blocking(move || manifest::verify_manifest(&dir)).await?;
+    Ok(match verification {
+        Ok(result) if result.is_valid() => Diagnostic::ok(
+            "mcp_install",
+            format!("MCP server installed in {}", mcp_server_dir.display()),
+        ),
+        Ok(result) => Diagnostic::warn(
+            "mcp_install",
+            format!(
+                "{} missing and {} modified file(s) compared to the install manifest",
+                result.missing.len(),
+                result.mismatched.len()
+            ),
+            "Run install_mcp_server with force to restore the bundled files",
+        ),
+        Err(e) => Diagnostic::warn(
+            "mcp_install",
+            format!("Could not verify the install manifest: {}", e),
+            "Run install_mcp_server with force to restore the bundled files",
+        ),
+    })
+}
+
+async fn diagnose_venv(app_handle: tauri::AppHandle) -> Result<Diagnostic, AppError> {
+    let Some(fastapi_dir) = resolve_fastapi_dir(&app_handle) else {
+        return Ok(Diagnostic::fail(
+            "fastapi_venv",
+            "FastAPI directory not found in resources",
+            "Reinstall the app",
+        ));
+    };
+    let venv_dir = fastapi_dir.join("venv");
+    if !venv_dir.exists() {
+        return Ok(Diagnostic::warn(
+            "fastapi_venv",
+            "FastAPI virtual environment has not been created yet",
+            "Run start_fastapi_server to create it",
+        ));
+    }
+
+    let venv_python = if cfg!(windows) {
+        venv_dir.join("Scripts/python.exe")
+    } else {
+        venv_dir.join("bin/python")
+    };
+    let valid = venv_dir.join("pyvenv.cfg").is_file()
+        && run_blocking(move || python_version_output(&venv_python).is_ok()).await?;
+    Ok(if valid {
+        Diagnostic::ok("fastapi_venv", format!("Virtual environment at {}", venv_dir.display()))
+    } else {
+        Diagnostic::fail(
+            "fastapi_venv",
+            format!("Virtual environment at {} is broken", venv_dir.display()),
+            "Run rebuild_fastapi_venv",
+        )
+    })
+}
+
+async fn diagnose_ports(app_handle: tauri::AppHandle) -> Result<Vec<Diagnostic>, AppError> {
+    run_blocking(move || {
+        let config = server_config(&app_handle);
+        let mut ports = vec![("fastapi", config.fastapi_port), ("mcp", config.mcp_port)];
+        if config.socket_port != config.mcp_port {
+            ports.push(("socket", config.socket_port));
+        }
+
+        ports
+            .into_iter()
+            .map(|(server, port)| {
+                let check = format!("{}_port", server);
+                if port_available(port) {
+                    return Diagnostic::ok(&check, format!("Port {} is free", port));
+                }
+                let ours = match server {
+                    "fastapi" => server_running(&app_handle, ManagedServer::FastApi),
+                    _ => port_owned_by_us(&app_handle, port),
+                };
+                if ours {
+                    let detail = format!("Port {} is used by our {} server", port, server);
+                    return Diagnostic::ok(&check, detail);
+                }
+                let holder = find_listening_pid(port)
+                    .map(|pid| match process_name(pid) {
+                        Some(name) => format!(" by {} (PID {})", name, pid),
+                        None => format!(" by PID {}", pid),
+                    })
+                    .unwrap_or_default();
+                Diagnostic::fail(
+                    &check,
+                    format!("Port {} is in use{}", port, holder),
+                    "Stop the other process (kill_orphaned_server) or choose another port",
+                )
+            })
+            .collect()
+    })
+    .await
+}
+
+#[tauri::command]
+async fn get_system_status(app_handle: tauri::AppHandle) -> Result<SystemStatus, AppError> {
+    let (bun, python, mcp_server, fastapi) = tokio::join!(
+        check_bun_installation(),
+        check_python_installation(app_handle.clone()),
+        get_mcp_server_status(app_handle.clone()),
+        get_fastapi_server_status(app_handle.clone()),
+    );
+
+    Ok(SystemStatus {
+        bun: bun?,
+        python: python?,
+        mcp_server: mcp_server?,
+        fastapi: fastapi?,
+    })
+}
+
+// Change the log level at runtime, e.g. to "debug" while reproducing a problem. Accepts
+// off, error, warn, info, debug or trace and returns the previous level.
+#[tauri::command]
+async fn set_log_level(level: String) -> Result<String, AppError> {
+    let new_level: log::LevelFilter = level.trim().parse().map_err(|_| AppError::InvalidArgument {
+        reason: format!(
+            "Unknown log level '{}' (expected off, error, warn, info, debug or trace)",
+            level
+        ),
+    })?;
+    let previous = log::max_level();
+    log::set_max_level(new_level);
+    log::info!("Log level changed from {} to {}", previous, new_level);
+    Ok(previous.as_str().to_lowercase())
+}
+
+// The last `last_n_lines` lines (default all buffered) a process wrote to stdout or
+// stderr, oldest first. Unlike get_recent_logs this reads the in-memory buffer, not the file.
+#[tauri::command]
+async fn get_process_logs(
+    app_handle: tauri::AppHandle,
+    process_id: String,
+    last_n_lines: Option<usize>,
+) -> Result<Vec<ProcessLogLine>, AppError> {
+    if !log_writer::LOG_SERVERS.contains(&process_id.as_str()) {
+        return Err(AppError::InvalidArgument {
+            reason: format!("Unknown process '{}'", process_id),
+        });
+    }
+    let logs = app_handle.state::<ProcessLogs>();
+    let logs = logs.lock().unwrap();
+    let Some(buffer) = logs.get(process_id.as_str()) else {
+        return Ok(Vec::new());
+    };
+    let skip = buffer.len().saturating_sub(last_n_lines.unwrap_or(PROCESS_LOG_LINES));
+    Ok(buffer.iter().skip(skip).cloned().collect())
+}
+
+#[tauri::command]
+async fn get_log_path(server: String) -> Result<String, AppError> {
+    log_writer::log_path(&server)
+        .map(|path| path.to_string_lossy().to_string())
+        .ok_or_else(|| AppError::InvalidArgument {
+            reason: format!("Unknown server '{}'", server),
+        })
+}
 
-    let pid = child.id();
-    log::info!("FastAPI server started with PID: {}", pid);
-    
-    // Store the process
-    {
-        let mut process = fastapi_process.lock().unwrap();
-        *process = Some(child);
+#[tauri::command]
+async fn get_recent_logs(server: String, lines: usize) -> Result<Vec<String>, AppError> {
+    let path = log_writer::log_path(&server).ok_or_else(|| AppError::InvalidArgument {
+        reason: format!("Unknown server '{}'", server),
+    })?;
+    if !path.exists() {
+        return Ok(Vec::new());
     }
 
-    Ok(format!("FastAPI server started with PID: {}", pid))
+    log_writer::read_tail(&path, lines)
+        .map_err(|e| AppError::io(format!("Failed to read {} log", server), e))
 }
 
+// Helper function to capture a tool's `--version` output for the diagnostics bundle
+fn tool_version(path: Result<String, AppError>) -> String {
+    let path = match path {
+        Ok(path) => path,
+        Err(e) => return e.to_string(),
+    };
+    match Command::new(&path).arg("--version").output() {
+        // Older Pythons print their version to stderr
+        Ok(output) => format!(
+            "{}: {}{}",
+            path,
+            String::from_utf8_lossy(&output.stdout).trim(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(e) => format!("{}: {}", path, e),
+    }
+}
+
+// Zip the server logs, installed version, redacted config and toolchain versions for support
 #[tauri::command]
-async fn stop_fastapi_server(app_handle: tauri::AppHandle) -> Result<String, String> {
-    let fastapi_process: FastAPIProcess = app_handle.state::<FastAPIProcess>().inner().clone();
-    
-    let mut process = fastapi_process.lock().unwrap();
-    if let Some(mut child) = process.take() {
-        match child.kill() {
-            Ok(_) => {
-                let _ = child.wait();
-                Ok("FastAPI server stopped".to_string())
-            }
-            Err(e) => Err(format!("Failed to stop FastAPI server: {}", e))
+async fn export_diagnostics(app_handle: tauri::AppHandle, dest: String) -> Result<String, AppError> {
+    // Only ever write the redacted config; secrets must not leave the machine
+    let config = server_config(&app_handle).redacted();
+    let config_json = serde_json::to_string_pretty(&config)
+        .map_err(|e| AppError::io("Failed to serialize server config", e))?;
+
+    let install_dir = {
+        let mcp_process = app_handle.state::<McpProcess>();
+        let install_dir = mcp_process.lock().unwrap().install_dir.clone();
+        install_dir
+    };
+    let version_json = mcp_server_dir(&app_handle, install_dir.as_deref())
+        .ok()
+        .and_then(|dir| std::fs::read_to_string(dir.join(VERSION_FILE)).ok())
+        .unwrap_or_else(|| "MCP server not installed".to_string());
+
+    // Running the toolchains and zipping the logs both block
+    run_blocking(move || {
+        let versions = format!(
+            "bun: {}\npython: {}\n",
+            tool_version(get_bun_path()),
+            tool_version(get_python_path(&app_handle))
+        );
+
+        let dest = std::path::PathBuf::from(dest);
+        let logs_dir = log_writer::logs_dir();
+        diagnostics::write_archive(
+            &dest,
+            logs_dir.as_deref(),
+            &[
+                ("server-config.json", config_json),
+                ("version.json", version_json),
+                ("versions.txt", versions),
+            ],
+        )
+        .map_err(|e| AppError::io(format!("Failed to write diagnostics to {:?}", dest), e))?;
+
+        Ok(dest.to_string_lossy().to_string())
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn reveal_install_dir(create_if_missing: Option<bool>) -> Result<String, AppError> {
+    let wirecraft_dir = wirecraft_dir()?;
+
+    if !wirecraft_dir.exists() {
+        if !create_if_missing.unwrap_or(true) {
+            return Err(AppError::not_found(format!(
+                "Install directory {}",
+                wirecraft_dir.display()
+            )));
         }
+        std::fs::create_dir_all(&wirecraft_dir)
+            .map_err(|e| AppError::io(format!("Failed to create {:?}", wirecraft_dir), e))?;
+    }
+
+    // The shell plugin's open() is deprecated, so hand the path to the platform's file manager
+    let opener = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
     } else {
-        Ok("FastAPI server is not running".to_string())
+        "xdg-open"
+    };
+    Command::new(opener)
+        .arg(&wirecraft_dir)
+        .spawn()
+        .map_err(|e| AppError::spawn(opener, e))?;
+
+    Ok(wirecraft_dir.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+async fn get_server_config(app_handle: tauri::AppHandle) -> Result<ServerConfig, AppError> {
+    Ok(server_config(&app_handle).redacted())
+}
+
+#[tauri::command]
+async fn set_server_config(
+    app_handle: tauri::AppHandle,
+    mut config: ServerConfig,
+    auto_apply: Option<bool>,
+) -> Result<ServerConfig, AppError> {
+    if config.fastapi_port == config.mcp_port || config.fastapi_port == config.socket_port {
+        return Err(AppError::InvalidArgument {
+            reason: format!(
+                "FastAPI port {} conflicts with an MCP server port",
+                config.fastapi_port
+            ),
+        });
+    }
+
+    for (what, url) in [
+        ("Health check URL", &config.health_check_url),
+        ("Bun registry", &config.bun_registry),
+    ] {
+        let Some(url) = url else { continue };
+        let valid = reqwest::Url::parse(url).is_ok_and(|parsed| {
+            matches!(parsed.scheme(), "http" | "https") && parsed.host_str().is_some()
+        });
+        if !valid {
+            return Err(AppError::InvalidArgument {
+                reason: format!("{} '{}' is not a valid http(s) URL", what, url),
+            });
+        }
+    }
+
+    validate_server_args("MCP server", &config.mcp_args, RESERVED_MCP_ARGS)?;
+    validate_server_args("FastAPI", &config.fastapi_args, RESERVED_FASTAPI_ARGS)?;
+
+    if config.fastapi_host.trim().is_empty() {
+        return Err(AppError::InvalidArgument {
+            reason: "FastAPI host must not be empty".to_string(),
+        });
+    }
+
+    // Reject an interpreter that can't run FastAPI now rather than at the next start
+    if let Some(python_path) = &config.python_path {
+        configured_python(python_path)?;
+    }
+
+    let (previous, updated) = {
+        let config_state = app_handle.state::<ServerConfigState>();
+        let mut current = config_state.lock().unwrap();
+        config.env = merge_env(&current.env, config.env);
+        // The frontend only ever sees the redacted token, so sending it back keeps the old one
+        if config.bun_registry_token.as_deref() == Some(REDACTED) {
+            config.bun_registry_token = current.bun_registry_token.clone();
+        }
+        let previous = std::mem::replace(&mut *current, config);
+        (previous, current.clone())
+    };
+    log::info!("Server configuration updated: {:?}", updated.redacted());
+
+    // Running servers keep their old settings until restarted, either now or by the user
+    let shared_changed = previous.mode != updated.mode || previous.env != updated.env;
+    let changed = [
+        (
+            ManagedServer::Mcp,
+            shared_changed
+                || previous.mcp_args != updated.mcp_args
+                || previous.mcp_port != updated.mcp_port
+                || previous.socket_port != updated.socket_port,
+        ),
+        (
+            ManagedServer::FastApi,
+            shared_changed
+                || previous.fastapi_port != updated.fastapi_port
+                || previous.fastapi_host != updated.fastapi_host
+                || previous.fastapi_args != updated.fastapi_args,
+        ),
+    ];
+    for (server, changed) in changed {
+        if !changed || !server_running(&app_handle, server) {
+            continue;
+        }
+        if auto_apply.unwrap_or(false) {
+            let msg = restart_with_new_config(&app_handle, server).await?;
+            log::info!("Restarted {} with the new configuration: {}", server.name(), msg);
+        } else {
+            mark_config_dirty(&app_handle, server);
+        }
+    }
+
+    Ok(updated.redacted())
+}
+
+// Flags the app controls through the environment; passing them as extra args would
+// silently disagree with the ports the app probes
+const RESERVED_MCP_ARGS: &[&str] = &["--port", "--socket-port"];
+const RESERVED_FASTAPI_ARGS: &[&str] = &["--port", "--host", "--reload"];
+
+// Helper function to check extra server args. No shell is involved, so metacharacters are
+// harmless, but control characters and the app's own flags are rejected.
+fn validate_server_args(server: &str, args: &[String], reserved: &[&str]) -> Result<(), AppError> {
+    for arg in args {
+        if arg.is_empty() || arg.chars().any(char::is_control) {
+            return Err(AppError::InvalidArgument {
+                reason: format!("Invalid {} argument {:?}", server, arg),
+            });
+        }
+        let flag = arg.split('=').next().unwrap_or(arg);
+        if reserved.contains(&flag) {
+            return Err(AppError::InvalidArgument {
+                reason: format!(
+                    "{} argument '{}' is managed by the app; change it in the server config instead",
+                    server, flag
+                ),
+            });
+        }
     }
+    Ok(())
+}
+
+// Helper function to flag a running server as using outdated settings
+fn mark_config_dirty(app_handle: &tauri::AppHandle, server: ManagedServer) {
+    let supervisor = app_handle.state::<Supervisor>();
+    supervisor.lock().unwrap().server_mut(server).config_dirty = true;
 }
 
+// Write the current configuration to ~/.wirecraft/config.json so it is loaded on the next launch
 #[tauri::command]
-async fn get_fastapi_server_status(app_handle: tauri::AppHandle) -> Result<FastAPIStatus, String> {
-    let fastapi_process: FastAPIProcess = app_handle.state::<FastAPIProcess>().inner().clone();
-    
-    let mut process = fastapi_process.lock().unwrap();
-    if let Some(child) = process.as_mut() {
-        match child.try_wait() {
-            Ok(None) => {
-                // Process is still running
-                Ok(FastAPIStatus {
-                    running: true,
-                    port: Some(8000),
-                    pid: Some(child.id()),
-                    health_check_url: Some("http://localhost:8000/health".to_string()),
-                })
-            }
-            Ok(Some(_)) => {
-                // Process has exited
-                *process = None;
-                Ok(FastAPIStatus {
-                    running: false,
-                    port: None,
-                    pid: None,
-                    health_check_url: None,
-                })
-            }
-            Err(e) => Err(format!("Failed to check process status: {}", e))
+async fn save_config(app_handle: tauri::AppHandle) -> Result<String, AppError> {
+    let path = write_config_file(&server_config(&app_handle))?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+// Helper function to write a configuration to the config file
+fn write_config_file(config: &ServerConfig) -> Result<std::path::PathBuf, AppError> {
+    let path = app_config_file_path()?;
+    config::save(&path, config)
+        .map_err(|e| AppError::io(format!("Failed to write {:?}", path), e))?;
+    Ok(path)
+}
+
+// The persisted app settings: ports, install location and the rest of ServerConfig
+#[tauri::command]
+async fn get_app_config(app_handle: tauri::AppHandle) -> Result<ServerConfig, AppError> {
+    get_server_config(app_handle).await
+}
+
+// Apply settings like set_server_config, then save them to ~/.wirecraft/config.json so
+// the ports and everything else survive a restart of the app
+#[tauri::command]
+async fn set_app_config(
+    app_handle: tauri::AppHandle,
+    config: ServerConfig,
+    auto_apply: Option<bool>,
+) -> Result<ServerConfig, AppError> {
+    let updated = set_server_config(app_handle.clone(), config, auto_apply).await?;
+    // Saved unredacted, like save_config; only the returned copy is masked
+    let stored = server_config(&app_handle);
+    run_blocking(move || write_config_file(&stored)).await??;
+    Ok(updated)
+}
+
+#[tauri::command]
+async fn get_auto_start(app_handle: tauri::AppHandle) -> Result<AutoStartSettings, AppError> {
+    let config = server_config(&app_handle);
+    Ok(AutoStartSettings {
+        auto_start: config.auto_start,
+        auto_install: config.auto_install,
+    })
+}
+
+// Change what the app does on launch. Saved to the config file straight away, since
+// the setting only matters at the next launch.
+#[tauri::command]
+async fn set_auto_start(
+    app_handle: tauri::AppHandle,
+    auto_start: Option<bool>,
+    auto_install: Option<bool>,
+) -> Result<AutoStartSettings, AppError> {
+    let config = {
+        let config_state = app_handle.state::<ServerConfigState>();
+        let mut config = config_state.lock().unwrap();
+        if let Some(auto_start) = auto_start {
+            config.auto_start = auto_start;
+        }
+        if let Some(auto_install) = auto_install {
+            config.auto_install = auto_install;
+        }
+        config.clone()
+    };
+    write_config_file(&config)?;
+    log::info!(
+        "Launch behavior updated: auto_start={}, auto_install={}",
+        config.auto_start,
+        config.auto_install
+    );
+
+    Ok(AutoStartSettings {
+        auto_start: config.auto_start,
+        auto_install: config.auto_install,
+    })
+}
+
+#[tauri::command]
+async fn get_server_env(app_handle: tauri::AppHandle) -> Result<HashMap<String, String>, AppError> {
+    Ok(redact_env(&server_config(&app_handle).env))
+}
+
+#[tauri::command]
+async fn set_server_env(
+    app_handle: tauri::AppHandle,
+    env: HashMap<String, String>,
+) -> Result<HashMap<String, String>, AppError> {
+    if let Some(name) = env.keys().find(|name| name.is_empty() || name.contains('=')) {
+        return Err(AppError::InvalidArgument {
+            reason: format!("Invalid environment variable name '{}'", name),
+        });
+    }
+
+    // Takes effect the next time each server is started
+    let config_state = app_handle.state::<ServerConfigState>();
+    let mut config = config_state.lock().unwrap();
+    config.env = merge_env(&config.env, env);
+    log::info!("Server environment updated: {:?}", redact_env(&config.env));
+    let env = redact_env(&config.env);
+    drop(config);
+
+    for server in [ManagedServer::Mcp, ManagedServer::FastApi] {
+        if server_running(&app_handle, server) {
+            mark_config_dirty(&app_handle, server);
         }
+    }
+    Ok(env)
+}
+
+#[tauri::command]
+async fn provision(
+    app_handle: tauri::AppHandle,
+    opts: ProvisionOptions,
+) -> Result<ProvisionReport, AppError> {
+    let mut report = ProvisionReport::default();
+
+    // Bun is a hard dependency of every MCP step
+    let bun_status = check_bun_installation().await?;
+    let bun_ready = if bun_status.installed {
+        report.skip("install_bun", "Bun is already installed");
+        true
+    } else if opts.skip_install {
+        report.skip("install_bun", "Skipped by request");
+        false
     } else {
-        Ok(FastAPIStatus {
-            running: false,
-            port: None,
-            pid: None,
-            health_check_url: None,
-        })
+        report.run("install_bun", install_bun(app_handle.clone(), None, None, None)).await
+    };
+
+    let mcp_installed = if !bun_ready {
+        report.skip("install_mcp_server", "Bun is unavailable");
+        false
+    } else if check_mcp_server_installation(app_handle.clone(), Some(true), None).await? && !opts.reinstall_mcp_server {
+        report.skip("install_mcp_server", "MCP server is already installed");
+        true
+    } else if opts.skip_install {
+        report.skip("install_mcp_server", "Skipped by request");
+        false
+    } else {
+        let reinstall = Some(opts.reinstall_mcp_server);
+        report
+            .run(
+                "install_mcp_server",
+                install_mcp_server_files(app_handle.clone(), None, reinstall, None),
+            )
+            .await
+    };
+
+    if opts.skip_start {
+        report.skip("start_mcp_server", "Skipped by request");
+    } else if mcp_installed {
+        report
+            .run("start_mcp_server", start_mcp_server(app_handle.clone(), None))
+            .await;
+    } else {
+        report.skip("start_mcp_server", "MCP server is not installed");
+    }
+
+    // FastAPI only needs Python, so it is still worth starting after an MCP failure
+    if opts.skip_fastapi || opts.skip_start {
+        report.skip("start_fastapi_server", "Skipped by request");
+    } else {
+        report
+            .run("start_fastapi_server", start_fastapi_server(app_handle.clone(), None))
+            .await;
     }
+
+    report.success = !report
+        .steps
+        .iter()
+        .any(|step| matches!(step.status, StepStatus::Failed));
+    Ok(report)
 }
 
+// The bootstrap the app runs on launch: install what is missing and start both servers
 #[tauri::command]
-async fn check_fastapi_health() -> Result<bool, String> {
-    // Try to make a health check request
-    use std::time::Duration;
-    
-    tokio::time::timeout(Duration::from_secs(5), async {
-        // Simple TCP connection check
-        tokio::net::TcpStream::connect("127.0.0.1:8000").await
-    })
-    .await
-    .map(|result| result.is_ok())
-    .unwrap_or(false)
-    .then_some(true)
-    .ok_or_else(|| "Health check failed".to_string())
+async fn setup_environment(app_handle: tauri::AppHandle) -> Result<SetupReport, AppError> {
+    provision(app_handle, ProvisionOptions::default()).await
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -528,72 +5657,517 @@ pub fn run() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_fs::init())
         .manage(FastAPIProcess::new(Mutex::new(None)))
+        .manage(McpProcess::default())
+        .manage(ServerConfigState::default())
+        .manage(Supervisor::default())
+        .manage(StderrTails::default())
+        .manage(ProcessLogs::default())
+        .manage(FastAPIBoundPort::default())
+        .manage(CurrentOperation::default())
+        .manage(InstallLock::default())
+        .manage(LastResolution::default())
         .setup(|app| {
-            if cfg!(debug_assertions) {
-                app.handle().plugin(
-                    tauri_plugin_log::Builder::default()
-                        .level(log::LevelFilter::Info)
-                        .build(),
-                )?;
-            }
-
-            // Auto-setup on app launch
-            let app_handle = app.handle().clone();
-            tauri::async_runtime::spawn(async move {
-                std::thread::sleep(std::time::Duration::from_secs(2));
-                
-                // Check if Bun is installed, install if not
-                if let Ok(bun_status) = check_bun_installation().await {
-                    if !bun_status.installed {
-                        log::info!("Bun not found, attempting to install...");
-                        if let Err(e) = install_bun().await {
-                            log::error!("Failed to install Bun: {}", e);
-                            return;
-                        }
-                    }
-                }
-
-                // Check if MCP server is installed, install if not
-                if let Ok(is_installed) = check_mcp_server_installation().await {
-                    if !is_installed {
-                        log::info!("MCP server not found, installing to ~/.wirecraft...");
-                        match install_mcp_server(app_handle.clone()).await {
-                            Ok(msg) => log::info!("Auto-installed MCP server: {}", msg),
-                            Err(e) => {
-                                log::error!("Failed to auto-install MCP server: {}", e);
-                                return;
-                            }
-                        }
-                    }
-                }
+            // Release builds keep warnings and errors so field reports still have logs
+            let level = if cfg!(debug_assertions) {
+                log::LevelFilter::Info
+            } else {
+                log::LevelFilter::Warn
+            };
+            // The dispatcher passes everything through; the global max level set below is
+            // the actual filter, so set_log_level can change it at runtime
+            let mut log_builder =
+                tauri_plugin_log::Builder::default().level(log::LevelFilter::Trace);
+            if json_log_format_enabled() {
+                log_builder = log_builder.format(format_json_log);
+            }
+            // Without logging the app is harder to debug, but the servers should still come up
+            if let Err(e) = app.handle().plugin(log_builder.build()) {
+                eprintln!("Failed to initialize logging, continuing without it: {}", e);
+            }
+            log::set_max_level(level);
 
-                // Start MCP server
-                match start_mcp_server().await {
-                    Ok(msg) => log::info!("Auto-started MCP server: {}", msg),
-                    Err(e) => log::error!("Failed to auto-start MCP server: {}", e),
-                }
+            // After the log plugin, so a malformed config file is reported
+            *app.state::<ServerConfigState>().lock().unwrap() = load_config_file();
 
-                // Start FastAPI server
-                match start_fastapi_server(app_handle.clone()).await {
-                    Ok(msg) => log::info!("Auto-started FastAPI server: {}", msg),
-                    Err(e) => log::error!("Failed to auto-start FastAPI server: {}", e),
-                }
-            });
+            // Watch for crashed and hung servers for the lifetime of the app
+            for server in [ManagedServer::Mcp, ManagedServer::FastApi] {
+                tauri::async_runtime::spawn(supervise_server(app.handle().clone(), server));
+                tauri::async_runtime::spawn(watch_server_health(app.handle().clone(), server));
+            }
 
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             check_bun_installation,
+            check_python_installation,
+            check_toolchain_shims,
+            get_runtime_versions,
             install_bun,
             install_mcp_server,
+            plan_mcp_server_install,
+            update_mcp_server,
+            check_mcp_update,
+            reinstall_mcp_dependencies,
             start_mcp_server,
+            stop_mcp_server,
+            reload_mcp_server,
+            check_mcp_ready,
+            last_resolution,
             check_mcp_server_installation,
+            get_installed_mcp_version,
+            get_mcp_package_info,
+            verify_mcp_server_install,
+            list_installed_files,
+            uninstall_mcp_server,
             get_mcp_server_status,
             start_fastapi_server,
             stop_fastapi_server,
+            rebuild_fastapi_venv,
+            stop_all,
             get_fastapi_server_status,
-            check_fastapi_health
+            check_fastapi_health,
+            check_mcp_socket_link,
+            get_system_status,
+            get_log_path,
+            get_process_logs,
+            set_log_level,
+            get_recent_logs,
+            reveal_install_dir,
+            export_diagnostics,
+            kill_orphaned_server,
+            free_our_port,
+            get_ports_in_use,
+            diagnose,
+            get_process_metrics,
+            cancel_current_operation,
+            get_server_config,
+            set_server_config,
+            get_app_config,
+            set_app_config,
+            save_config,
+            get_auto_start,
+            set_auto_start,
+            get_server_env,
+            set_server_env,
+            provision,
+            setup_environment
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| match event {
+            // Bootstrap once the event loop and windows are up rather than after a fixed sleep
+            tauri::RunEvent::Ready => {
+                tauri::async_runtime::spawn(auto_setup(app_handle.clone()));
+            }
+            // Exit fires once, as the app is really exiting; ExitRequested may still be prevented
+            tauri::RunEvent::Exit => {
+                shutdown_managed_processes(app_handle);
+            }
+            _ => {}
+        });
+}
+
+// Auto-setup on app launch. WIRECRAFT_STARTUP_DELAY_MS adds an optional extra
+// delay for machines where the servers need more time after the window opens.
+async fn auto_setup(app_handle: tauri::AppHandle) {
+    let delay_ms = std::env::var("WIRECRAFT_STARTUP_DELAY_MS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(0);
+    if delay_ms > 0 {
+        tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+    }
+
+    let config = server_config(&app_handle);
+    if !config.auto_install && !config.auto_start {
+        log::info!("Auto-setup disabled; servers are left stopped");
+        return;
+    }
+    let opts = ProvisionOptions {
+        skip_install: !config.auto_install,
+        skip_start: !config.auto_start,
+        ..ProvisionOptions::default()
+    };
+
+    match provision(app_handle, opts).await {
+        Ok(report) => {
+            for step in &report.steps {
+                match step.status {
+                    StepStatus::Failed => {
+                        log::error!("Auto-setup {} failed: {}", step.name, step.detail)
+                    }
+                    _ => log::info!(
+                        "Auto-setup {} {:?}: {}",
+                        step.name,
+                        step.status,
+                        step.detail
+                    ),
+                }
+            }
+        }
+        Err(e) => log::error!("Auto-setup failed: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn mkdirs(root: &Path, rel: &str) -> std::path::PathBuf {
+        let dir = root.join(rel);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // Name of the Bun executable the installers create on this platform
+    const BUN_EXE: &str = if cfg!(windows) { "bun.exe" } else { "bun" };
+
+    // Helper function to put an empty stand-in for the Bun executable in `dir`
+    fn fake_bun(dir: &Path) -> std::path::PathBuf {
+        std::fs::create_dir_all(dir).unwrap();
+        let bun = dir.join(BUN_EXE);
+        std::fs::write(&bun, "").unwrap();
+        bun
+    }
+
+    #[test]
+    fn bun_found_in_home_dot_bun() {
+        let home = tempfile::tempdir().unwrap();
+        let expected = fake_bun(&home.path().join(".bun/bin"));
+
+        let found = find_installed_bun(bun_install_candidates(Some(home.path()), None));
+        assert_eq!(found.unwrap(), expected.to_string_lossy());
+    }
+
+    #[test]
+    fn bun_install_takes_precedence_over_home() {
+        let home = tempfile::tempdir().unwrap();
+        fake_bun(&home.path().join(".bun/bin"));
+        let bun_install = tempfile::tempdir().unwrap();
+        let expected = fake_bun(&bun_install.path().join("bin"));
+
+        let found = find_installed_bun(bun_install_candidates(
+            Some(home.path()),
+            Some(&bun_install.path().to_string_lossy()),
+        ));
+        assert_eq!(found.unwrap(), expected.to_string_lossy());
+    }
+
+    #[test]
+    fn bun_not_found_in_empty_home() {
+        let home = tempfile::tempdir().unwrap();
+
+        let candidates = bun_install_candidates(Some(home.path()), None);
+        assert!(candidates.contains(&home.path().join(".bun/bin").join(BUN_EXE)));
+        // Only the home candidate is checked off-Windows; Windows adds system-wide locations
+        if !cfg!(windows) {
+            assert!(matches!(find_installed_bun(candidates), Err(AppError::BunNotFound)));
+        }
+    }
+
+    #[test]
+    fn bun_installed_mid_session_is_found_on_next_lookup() {
+        let home = tempfile::tempdir().unwrap();
+        let missing_bun_install = home.path().join("no-such-bun-install");
+        let bun_install = missing_bun_install.to_string_lossy();
+        let lookup = || {
+            find_installed_bun(bun_install_candidates(Some(home.path()), Some(&bun_install)))
+        };
+        if !cfg!(windows) {
+            assert!(matches!(lookup(), Err(AppError::BunNotFound)));
+        }
+
+        // install_bun leaves bun only in ~/.bun/bin, which isn't on our PATH
+        let expected = fake_bun(&home.path().join(".bun/bin"));
+        assert_eq!(lookup().unwrap(), expected.to_string_lossy());
+    }
+
+    // Helper function to write `contents` at `rel` under `root`, creating parent directories
+    fn write_file(root: &Path, rel: &str, contents: &str) {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn copy_skips_ignored_dirs_and_keeps_siblings() {
+        let src = tempfile::tempdir().unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        write_file(src.path(), "server.ts", "server");
+        write_file(src.path(), "node_modules/pkg/index.js", "dep");
+        write_file(src.path(), ".git/HEAD", "ref");
+        write_file(src.path(), "dist/server.js", "built");
+        write_file(src.path(), "lib/util.ts", "util");
+        write_file(src.path(), "lib/node_modules/nested/index.js", "dep");
+        write_file(src.path(), "lib/dist/out.js", "built");
+        // Only directories are ignored, so a file that shares a name is still copied
+        write_file(src.path(), "lib/dist.txt", "notes");
+
+        let mut copied = 0;
+        copy_dir_recursive(src.path(), dst.path(), &is_bundle_file, SymlinkPolicy::Skip, &mut || {
+            copied += 1
+        })
+        .unwrap();
+
+        for kept in ["server.ts", "lib/util.ts", "lib/dist.txt"] {
+            assert!(dst.path().join(kept).is_file(), "{} should be copied", kept);
+        }
+        for skipped in ["node_modules", ".git", "dist", "lib/node_modules", "lib/dist"] {
+            assert!(!dst.path().join(skipped).exists(), "{} should be skipped", skipped);
+        }
+        assert_eq!(copied, 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copy_does_not_follow_symlink_to_parent() {
+        for policy in [SymlinkPolicy::Skip, SymlinkPolicy::Preserve] {
+            let src = tempfile::tempdir().unwrap();
+            let dst = tempfile::tempdir().unwrap();
+            write_file(src.path(), "lib/util.ts", "util");
+            std::os::unix::fs::symlink("..", src.path().join("lib/parent")).unwrap();
+
+            // Following the link would recurse into lib/parent/lib/parent/... until it failed
+            copy_dir_recursive(src.path(), dst.path(), &is_bundle_file, policy, &mut || {})
+                .unwrap();
+
+            assert!(dst.path().join("lib/util.ts").is_file());
+            let link = dst.path().join("lib/parent");
+            match policy {
+                SymlinkPolicy::Skip => assert!(link.symlink_metadata().is_err()),
+                SymlinkPolicy::Preserve => {
+                    assert!(link.symlink_metadata().unwrap().file_type().is_symlink());
+                    assert_eq!(std::fs::read_link(&link).unwrap(), Path::new(".."));
+                }
+            }
+        }
+    }
+
+    // The only test that touches HOME; everything else takes the home dir as a parameter
+    #[test]
+    fn home_directory_resolves_without_home_env() {
+        let saved = std::env::var_os("HOME");
+        std::env::remove_var("HOME");
+        // dirs falls back to the passwd entry on Unix and the known-folder API on Windows
+        let home = home_directory();
+        if let Some(saved) = saved {
+            std::env::set_var("HOME", saved);
+        }
+
+        let home = home.expect("home directory should resolve without $HOME");
+        assert!(home.is_absolute(), "{:?}", home);
+    }
+
+    // Helper function to check a process is gone. An orphaned worker may linger as a zombie
+    // when PID 1 doesn't reap (e.g. in containers), which counts as gone.
+    #[cfg(unix)]
+    fn process_gone(pid: u32) -> bool {
+        let zombie = std::fs::read_to_string(format!("/proc/{}/stat", pid))
+            .ok()
+            .and_then(|stat| stat.rsplit(')').next().map(|rest| rest.trim_start().starts_with('Z')))
+            .unwrap_or(false);
+        !process_alive(pid) || zombie
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn terminate_process_group_stops_leader_and_forked_worker() {
+        use std::io::BufRead;
+
+        // The worker ignores SIGTERM, like a uvicorn reload worker that outlives its parent
+        let mut command = Command::new("sh");
+        command
+            .args(["-c", "sh -c 'trap \"\" TERM; sleep 30' & echo $!; wait"])
+            .stdout(Stdio::piped());
+        configure_process_group(&mut command);
+        let mut leader = command.spawn().unwrap();
+        let mut line = String::new();
+        std::io::BufReader::new(leader.stdout.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let worker_pid: u32 = line.trim().parse().unwrap();
+        assert!(process_alive(worker_pid));
+
+        terminate_process_group(&mut leader, std::time::Duration::from_millis(300)).unwrap();
+
+        assert!(leader.try_wait().unwrap().is_some(), "leader should have exited");
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(3);
+        while !process_gone(worker_pid) && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        assert!(process_gone(worker_pid), "worker {} should have exited", worker_pid);
+    }
+
+    #[test]
+    fn uninstall_refuses_configured_dir_without_marker() {
+        let wirecraft = tempfile::tempdir().unwrap();
+        let documents = tempfile::tempdir().unwrap();
+        write_file(documents.path(), "notes.txt", "keep me");
+
+        let result = removable_install_dir(documents.path(), wirecraft.path(), None);
+        assert!(matches!(result, Err(AppError::InvalidArgument { .. })), "{:?}", result);
+    }
+
+    #[test]
+    fn uninstall_allows_configured_dir_with_marker() {
+        let wirecraft = tempfile::tempdir().unwrap();
+        for marker in [VERSION_FILE, manifest::MANIFEST_FILE] {
+            let install = tempfile::tempdir().unwrap();
+            write_file(install.path(), marker, "{}");
+
+            let resolved = removable_install_dir(install.path(), wirecraft.path(), None).unwrap();
+            assert_eq!(resolved, install.path().canonicalize().unwrap());
+        }
+    }
+
+    #[test]
+    fn uninstall_allows_unmarked_dir_inside_wirecraft_dir() {
+        let wirecraft = tempfile::tempdir().unwrap();
+        let install = mkdirs(wirecraft.path(), "mcp-server");
+
+        assert!(removable_install_dir(&install, wirecraft.path(), None).is_ok());
+        // ~/.wirecraft itself is never an install
+        assert!(removable_install_dir(wirecraft.path(), wirecraft.path(), None).is_err());
+    }
+
+    #[test]
+    fn uninstall_refuses_home_dir_even_with_marker() {
+        let wirecraft = tempfile::tempdir().unwrap();
+        let home = tempfile::tempdir().unwrap();
+        write_file(home.path(), VERSION_FILE, "{}");
+
+        assert!(removable_install_dir(home.path(), wirecraft.path(), Some(home.path())).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn shutdown_stops_every_child_in_parallel() {
+        // Both ignore SIGTERM, so each one takes the whole grace period before being killed
+        let spawn = |process_group: bool| {
+            let mut command = Command::new("sh");
+            command.args(["-c", "trap '' TERM; while :; do sleep 0.1; done"]);
+            if process_group {
+                configure_process_group(&mut command);
+            }
+            ShutdownTarget {
+                name: "dummy",
+                child: command.spawn().unwrap(),
+                process_group,
+            }
+        };
+        let targets = vec![spawn(false), spawn(true)];
+        let mut pids: Vec<u32> = targets.iter().map(|target| target.child.id()).collect();
+        assert!(pids.iter().all(|pid| process_alive(*pid)));
+
+        let grace_period = std::time::Duration::from_millis(500);
+        let started = std::time::Instant::now();
+        let mut stopped = terminate_children(targets, grace_period);
+
+        assert!(started.elapsed() < grace_period * 2, "took {:?}", started.elapsed());
+        stopped.sort();
+        pids.sort();
+        assert_eq!(stopped, pids);
+        for pid in pids {
+            assert!(process_gone(pid), "PID {} should have exited", pid);
+        }
+    }
+
+    #[test]
+    fn bundle_resolves_directly_under_resource_dir() {
+        let tmp = tempfile::tempdir().unwrap();
+        let resource = mkdirs(tmp.path(), "app/resources");
+        let expected = mkdirs(&resource, "mcp-server-bundle");
+        let cwd = mkdirs(tmp.path(), "elsewhere/tauri-mcp-client/src-tauri");
+
+        let found = first_existing(bundle_candidates(
+            Some(&resource),
+            Some(&cwd),
+            "mcp-server-bundle",
+        ));
+        assert_eq!(found, Some(expected));
+    }
+
+    #[test]
+    fn bundle_resolves_under_resource_up_up() {
+        let tmp = tempfile::tempdir().unwrap();
+        let resource = mkdirs(tmp.path(), "app/resources");
+        let expected = mkdirs(&resource, "_up_/_up_/mcp-server-bundle");
+        let cwd = mkdirs(tmp.path(), "elsewhere/tauri-mcp-client/src-tauri");
+
+        let found = first_existing(bundle_candidates(
+            Some(&resource),
+            Some(&cwd),
+            "mcp-server-bundle",
+        ));
+        assert_eq!(found, Some(expected));
+    }
+
+    #[test]
+    fn bundle_resolves_relative_to_dev_checkout() {
+        let tmp = tempfile::tempdir().unwrap();
+        let resource = mkdirs(tmp.path(), "target/debug");
+        let cwd = mkdirs(tmp.path(), "repo/tauri-mcp-client/src-tauri");
+        let expected = mkdirs(tmp.path(), "repo/mcp-server-bundle");
+
+        let found = first_existing(bundle_candidates(
+            Some(&resource),
+            Some(&cwd),
+            "mcp-server-bundle",
+        ));
+        assert_eq!(found, Some(expected));
+    }
+
+    #[test]
+    fn bundle_prefers_resource_dir_over_dev_checkout() {
+        let tmp = tempfile::tempdir().unwrap();
+        let resource = mkdirs(tmp.path(), "app/resources");
+        let expected = mkdirs(&resource, "mcp-server-bundle");
+        mkdirs(&resource, "_up_/_up_/mcp-server-bundle");
+        let cwd = mkdirs(tmp.path(), "repo/tauri-mcp-client/src-tauri");
+        mkdirs(tmp.path(), "repo/mcp-server-bundle");
+
+        let found = first_existing(bundle_candidates(
+            Some(&resource),
+            Some(&cwd),
+            "mcp-server-bundle",
+        ));
+        assert_eq!(found, Some(expected));
+    }
+
+    #[test]
+    fn bundle_not_found_in_empty_layout() {
+        let tmp = tempfile::tempdir().unwrap();
+        let resource = mkdirs(tmp.path(), "app/resources");
+        let cwd = mkdirs(tmp.path(), "repo/tauri-mcp-client/src-tauri");
+
+        let found = first_existing(bundle_candidates(
+            Some(&resource),
+            Some(&cwd),
+            "mcp-server-bundle",
+        ));
+        assert_eq!(found, None);
+    }
+
+    #[test]
+    fn fastapi_resolves_next_to_dev_src_tauri() {
+        let tmp = tempfile::tempdir().unwrap();
+        let resource = mkdirs(tmp.path(), "app/resources");
+        let cwd = mkdirs(tmp.path(), "repo/tauri-mcp-client/src-tauri");
+        let expected = mkdirs(tmp.path(), "repo/tauri-mcp-client/resource/mcp-client-python/api");
+
+        let found = first_existing(fastapi_candidates(Some(&resource), Some(&cwd)));
+        assert_eq!(found, Some(expected));
+    }
+
+    #[test]
+    fn fastapi_resolves_beside_resource_dir() {
+        let tmp = tempfile::tempdir().unwrap();
+        let resource = mkdirs(tmp.path(), "app/resources");
+        let expected = mkdirs(tmp.path(), "app/resource/mcp-client-python/api");
+        let cwd = mkdirs(tmp.path(), "elsewhere/src-tauri");
+
+        let found = first_existing(fastapi_candidates(Some(&resource), Some(&cwd)));
+        assert_eq!(found, Some(expected));
+    }
 }
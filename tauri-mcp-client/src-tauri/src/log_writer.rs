@@ -0,0 +1,102 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+pub const DEFAULT_MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+
+// Servers whose output is persisted; also guards log lookups against path traversal
+pub const LOG_SERVERS: &[&str] = &["mcp-server", "socket-server", "fastapi"];
+
+// Appends captured server output to ~/.wirecraft/logs/<server>.log, rotating to .1
+pub struct LogWriter {
+    path: PathBuf,
+    file: File,
+    written: u64,
+    max_bytes: u64,
+}
+
+impl LogWriter {
+    pub fn open(path: PathBuf, max_bytes: u64) -> std::io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+
+        Ok(Self {
+            path,
+            file,
+            written,
+            max_bytes,
+        })
+    }
+
+    pub fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        if self.written > 0 && self.written + line.len() as u64 + 1 > self.max_bytes {
+            self.rotate()?;
+        }
+
+        writeln!(self.file, "{}", line)?;
+        // Flush every line so a crash doesn't lose the last output
+        self.file.flush()?;
+        self.written += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        let mut rotated = self.path.clone().into_os_string();
+        rotated.push(".1");
+        std::fs::rename(&self.path, &rotated)?;
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+pub fn logs_dir() -> Option<PathBuf> {
+    Some(crate::wirecraft_dir().ok()?.join("logs"))
+}
+
+pub fn log_path(server: &str) -> Option<PathBuf> {
+    if !LOG_SERVERS.contains(&server) {
+        return None;
+    }
+    Some(logs_dir()?.join(format!("{}.log", server)))
+}
+
+// Returns the last `lines` lines of a log, reading backwards in chunks so large
+// logs aren't loaded whole
+pub fn read_tail(path: &std::path::Path, lines: usize) -> std::io::Result<Vec<String>> {
+    const CHUNK: u64 = 8 * 1024;
+
+    if lines == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut pos = len;
+    let mut buf = Vec::new();
+
+    // One extra newline is needed to know the earliest wanted line is complete
+    while pos > 0 && buf.iter().filter(|&&b| b == b'\n').count() <= lines {
+        let read = CHUNK.min(pos);
+        pos -= read;
+        file.seek(SeekFrom::Start(pos))?;
+        let mut chunk = vec![0; read as usize];
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&buf);
+        buf = chunk;
+    }
+
+    let text = String::from_utf8_lossy(&buf);
+    let all: Vec<&str> = text.lines().collect();
+    // Drop the partial first line when we stopped reading mid-file
+    let complete = if pos > 0 { &all[1.min(all.len())..] } else { &all[..] };
+    let start = complete.len().saturating_sub(lines);
+    Ok(complete[start..].iter().map(|line| line.to_string()).collect())
+}
@@ -0,0 +1,114 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Debug, Default, Serialize)]
+pub struct ManifestVerification {
+    pub missing: Vec<String>,
+    pub mismatched: Vec<String>,
+}
+
+impl ManifestVerification {
+    pub fn is_valid(&self) -> bool {
+        self.missing.is_empty() && self.mismatched.is_empty()
+    }
+}
+
+pub fn hash_file(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+fn collect_hashes(
+    root: &Path,
+    dir: &Path,
+    include: &dyn Fn(&Path) -> bool,
+    hashes: &mut BTreeMap<String, String>,
+) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if !include(&path) {
+            continue;
+        }
+
+        // Symlinks are never followed, matching how installs copy the bundle
+        let file_type = entry.file_type()?;
+        if file_type.is_symlink() {
+            continue;
+        }
+        if file_type.is_dir() {
+            collect_hashes(root, &path, include, hashes)?;
+            continue;
+        }
+
+        let relative = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        if relative == MANIFEST_FILE {
+            continue;
+        }
+        hashes.insert(relative, hash_file(&path)?);
+    }
+    Ok(())
+}
+
+// Hashes every file under `source` accepted by `include`, keyed by '/'-separated relative path
+pub fn hash_bundle(
+    source: &Path,
+    include: &dyn Fn(&Path) -> bool,
+) -> std::io::Result<BTreeMap<String, String>> {
+    let mut hashes = BTreeMap::new();
+    collect_hashes(source, source, include, &mut hashes)?;
+    Ok(hashes)
+}
+
+pub fn save_manifest(install_dir: &Path, hashes: &BTreeMap<String, String>) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(hashes)?;
+    std::fs::write(install_dir.join(MANIFEST_FILE), json)
+}
+
+// Hashes every bundled file under `source` that was copied (per `include`) and
+// writes the manifest into `install_dir`
+pub fn write_manifest(
+    source: &Path,
+    install_dir: &Path,
+    include: &dyn Fn(&Path) -> bool,
+) -> std::io::Result<()> {
+    save_manifest(install_dir, &hash_bundle(source, include)?)
+}
+
+pub fn read_manifest(install_dir: &Path) -> std::io::Result<BTreeMap<String, String>> {
+    let contents = std::fs::read_to_string(install_dir.join(MANIFEST_FILE))?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+pub fn verify_manifest(install_dir: &Path) -> std::io::Result<ManifestVerification> {
+    let hashes = read_manifest(install_dir)?;
+
+    let mut result = ManifestVerification::default();
+    for (relative, expected) in hashes {
+        let path = install_dir.join(&relative);
+        if !path.is_file() {
+            result.missing.push(relative);
+            continue;
+        }
+        if hash_file(&path)? != expected {
+            result.mismatched.push(relative);
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,213 @@
+// Process management primitives shared by the MCP and FastAPI servers. Nothing here
+// depends on the Tauri runtime, so the integration tests drive it against a mock server.
+
+use std::process::{Child, Command};
+
+// Helper function to check that nothing is already listening on a local port
+pub fn port_available(port: u16) -> bool {
+    std::net::TcpListener::bind(("127.0.0.1", port)).is_ok()
+}
+
+// Helper function to stop a child process, asking it to exit cleanly first on Unix.
+// Returns true if the process exited on its own rather than being killed.
+pub fn terminate_child(child: &mut Child, grace_period: std::time::Duration) -> std::io::Result<bool> {
+    if child.try_wait()?.is_some() {
+        return Ok(true);
+    }
+
+    #[cfg(unix)]
+    {
+        unsafe {
+            libc::kill(child.id() as libc::pid_t, libc::SIGTERM);
+        }
+        let deadline = std::time::Instant::now() + grace_period;
+        while std::time::Instant::now() < deadline {
+            if child.try_wait()?.is_some() {
+                return Ok(true);
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = grace_period;
+
+    child.kill()?;
+    child.wait()?;
+    Ok(false)
+}
+
+// Helper function to stop a child spawned as a process group leader together with
+// everything it forked (uvicorn reload workers). Returns true if the leader exited cleanly.
+pub fn terminate_process_group(
+    child: &mut Child,
+    grace_period: std::time::Duration,
+) -> std::io::Result<bool> {
+    let pid = child.id();
+
+    #[cfg(unix)]
+    {
+        let graceful = if child.try_wait()?.is_some() {
+            true
+        } else {
+            unsafe {
+                libc::killpg(pid as libc::pid_t, libc::SIGTERM);
+            }
+            let deadline = std::time::Instant::now() + grace_period;
+            loop {
+                if child.try_wait()?.is_some() {
+                    break true;
+                }
+                if std::time::Instant::now() >= deadline {
+                    break false;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(50));
+            }
+        };
+        // Workers that ignored SIGTERM or outlived the leader go down with the group
+        kill_process_group(pid);
+        if !graceful {
+            child.wait()?;
+        }
+        Ok(graceful)
+    }
+
+    #[cfg(not(unix))]
+    {
+        // taskkill /T has no graceful mode for console processes, so the tree is killed outright
+        let _ = grace_period;
+        let exited = child.try_wait()?.is_some();
+        kill_process_group(pid);
+        if exited {
+            return Ok(true);
+        }
+        child.kill().ok();
+        child.wait()?;
+        Ok(false)
+    }
+}
+
+// Helper function to force-kill whatever is left of a process group or tree
+pub fn kill_process_group(pid: u32) {
+    #[cfg(unix)]
+    unsafe {
+        libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+    }
+    #[cfg(windows)]
+    {
+        let _ = Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &pid.to_string()])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status();
+    }
+}
+
+// Helper function to check whether a process with the given PID still exists
+pub fn process_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        // Signal 0 performs the permission and existence checks without signalling
+        let result = unsafe { libc::kill(pid as libc::pid_t, 0) };
+        result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+    }
+    #[cfg(not(unix))]
+    {
+        Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+            .unwrap_or(false)
+    }
+}
+
+// Helper function to find the PID listening on a local TCP port
+pub fn find_listening_pid(port: u16) -> Option<u32> {
+    #[cfg(unix)]
+    {
+        let lsof = Command::new("lsof")
+            .args(["-nP", &format!("-iTCP:{}", port), "-sTCP:LISTEN", "-t"])
+            .output()
+            .ok()
+            .and_then(|output| {
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .find_map(|line| line.trim().parse().ok())
+            });
+        if lsof.is_some() {
+            return lsof;
+        }
+
+        // lsof isn't installed on every Linux distro; ss reports `users:(("bun",pid=123,fd=4))`
+        let output = Command::new("ss")
+            .args(["-ltnpH", &format!("sport = :{}", port)])
+            .output()
+            .ok()?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let pid = stdout.split("pid=").nth(1)?;
+        pid.split(|c: char| !c.is_ascii_digit()).next()?.parse().ok()
+    }
+    #[cfg(not(unix))]
+    {
+        // `  TCP    0.0.0.0:8000    0.0.0.0:0    LISTENING    1234`
+        let output = Command::new("netstat").args(["-ano", "-p", "TCP"]).output().ok()?;
+        let suffix = format!(":{}", port);
+        String::from_utf8_lossy(&output.stdout).lines().find_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                [_, local, _, "LISTENING", pid] if local.ends_with(&suffix) => pid.parse().ok(),
+                _ => None,
+            }
+        })
+    }
+}
+
+// Helper function to make a spawned server the leader of its own process group, so stopping
+// it also reaches anything it forks (uvicorn reload workers)
+pub fn configure_process_group(command: &mut Command) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        command.creation_flags(CREATE_NEW_PROCESS_GROUP);
+    }
+}
+
+// Helper function to GET a health endpoint and insist on a `{"status": "healthy"}` body.
+// Returns the body, or the reason the check failed.
+pub async fn probe_health(
+    url: &str,
+    timeout: std::time::Duration,
+) -> Result<serde_json::Value, String> {
+    let client = reqwest::Client::builder()
+        .timeout(timeout)
+        .build()
+        .map_err(|e| format!("could not create HTTP client: {}", e))?;
+
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("request failed: {}", e))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("HTTP status {}", status));
+    }
+
+    // A squatter on the port may answer 2xx too, so insist on the FastAPI payload
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("invalid JSON: {}", e))?;
+
+    if body.get("status").and_then(|s| s.as_str()) == Some("healthy") {
+        Ok(body)
+    } else {
+        Err(format!("unexpected body: {}", body))
+    }
+}
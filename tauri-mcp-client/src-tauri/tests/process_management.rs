@@ -0,0 +1,180 @@
+// Exercises the process management primitives against examples/mock_server.rs, which
+// `cargo test` builds alongside this suite, so neither Bun nor Python is needed.
+
+use app_lib::process::{
+    configure_process_group, find_listening_pid, port_available, probe_health, process_alive,
+    terminate_child, terminate_process_group,
+};
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+const GRACE_PERIOD: Duration = Duration::from_secs(3);
+
+struct MockServer {
+    child: Child,
+    port: u16,
+    worker_pid: Option<u32>,
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        let _ = terminate_process_group(&mut self.child, Duration::ZERO);
+    }
+}
+
+// Examples land next to the deps directory this test binary runs from
+fn mock_server_path() -> std::path::PathBuf {
+    let exe = std::env::current_exe().unwrap();
+    let target_dir = exe.parent().and_then(|deps| deps.parent()).unwrap();
+    target_dir
+        .join("examples")
+        .join(format!("mock_server{}", std::env::consts::EXE_SUFFIX))
+}
+
+// Spawns the mock the way start_fastapi_server spawns main.py and waits for its startup line
+fn spawn_mock(args: &[&str]) -> MockServer {
+    let mut command = Command::new(mock_server_path());
+    command
+        .args(args)
+        .env("PORT", "0")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    configure_process_group(&mut command);
+    let mut child = command.spawn().expect("mock server should spawn");
+
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+    let mut worker_pid = None;
+    let port = loop {
+        let line = lines
+            .next()
+            .expect("mock server exited before binding")
+            .unwrap();
+        if let Some(pid) = line.strip_prefix("Started worker process [") {
+            worker_pid = pid.trim_end_matches(']').parse().ok();
+        }
+        if let Some((_, port)) = line.split_once("127.0.0.1:") {
+            break port.split_whitespace().next().unwrap().parse().unwrap();
+        }
+    };
+
+    MockServer {
+        child,
+        port,
+        worker_pid,
+    }
+}
+
+fn wait_until(timeout: Duration, mut condition: impl FnMut() -> bool) -> bool {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if condition() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    condition()
+}
+
+fn health_url(port: u16) -> String {
+    format!("http://127.0.0.1:{}/health", port)
+}
+
+#[test]
+fn started_server_holds_its_port() {
+    let server = spawn_mock(&[]);
+
+    assert!(!port_available(server.port));
+    assert!(process_alive(server.child.id()));
+    // lsof/ss/netstat may be missing on minimal CI images
+    if let Some(pid) = find_listening_pid(server.port) {
+        assert_eq!(pid, server.child.id());
+    }
+}
+
+#[tokio::test]
+async fn health_check_accepts_healthy_payload() {
+    let server = spawn_mock(&[]);
+
+    probe_health(&health_url(server.port), GRACE_PERIOD)
+        .await
+        .expect("mock server should report healthy");
+}
+
+#[tokio::test]
+async fn health_check_rejects_unexpected_payload() {
+    let server = spawn_mock(&["--unhealthy"]);
+
+    let reason = probe_health(&health_url(server.port), GRACE_PERIOD)
+        .await
+        .unwrap_err();
+    assert!(reason.contains("unexpected body"), "{}", reason);
+}
+
+#[tokio::test]
+async fn health_check_fails_once_stopped() {
+    let mut server = spawn_mock(&[]);
+    let url = health_url(server.port);
+    terminate_process_group(&mut server.child, GRACE_PERIOD).unwrap();
+
+    let reason = probe_health(&url, GRACE_PERIOD).await.unwrap_err();
+    assert!(reason.contains("request failed"), "{}", reason);
+}
+
+#[test]
+fn try_wait_reports_a_crashed_server() {
+    let mut server = spawn_mock(&["--exit-after", "200"]);
+
+    assert!(wait_until(GRACE_PERIOD, || matches!(
+        server.child.try_wait(),
+        Ok(Some(_))
+    )));
+    let status = server.child.try_wait().unwrap().unwrap();
+    assert_eq!(status.code(), Some(3));
+    assert!(port_available(server.port));
+    // Stopping an already exited server counts as a clean exit
+    assert!(terminate_child(&mut server.child, GRACE_PERIOD).unwrap());
+}
+
+#[test]
+fn terminate_frees_the_port() {
+    let mut server = spawn_mock(&[]);
+    let pid = server.child.id();
+
+    terminate_process_group(&mut server.child, GRACE_PERIOD).unwrap();
+
+    assert!(server.child.try_wait().unwrap().is_some());
+    assert!(wait_until(GRACE_PERIOD, || port_available(server.port)));
+    assert!(wait_until(GRACE_PERIOD, || !process_alive(pid)));
+}
+
+#[cfg(unix)]
+#[test]
+fn terminate_exits_gracefully_on_sigterm() {
+    let mut server = spawn_mock(&[]);
+
+    assert!(terminate_child(&mut server.child, GRACE_PERIOD).unwrap());
+}
+
+#[cfg(unix)]
+#[test]
+fn terminate_escalates_to_kill_when_sigterm_is_ignored() {
+    let mut server = spawn_mock(&["--ignore-term"]);
+
+    let graceful = terminate_child(&mut server.child, Duration::from_millis(300)).unwrap();
+
+    assert!(!graceful);
+    assert!(server.child.try_wait().unwrap().is_some());
+    assert!(wait_until(GRACE_PERIOD, || port_available(server.port)));
+}
+
+#[test]
+fn terminate_process_group_reaches_forked_workers() {
+    let mut server = spawn_mock(&["--spawn-worker", "--ignore-term"]);
+    let worker_pid = server.worker_pid.expect("mock server should report its worker");
+    assert!(process_alive(worker_pid));
+
+    terminate_process_group(&mut server.child, Duration::from_millis(300)).unwrap();
+
+    assert!(wait_until(GRACE_PERIOD, || !process_alive(worker_pid)));
+}
@@ -61,6 +61,16 @@ export default function FastAPIManager() {
     loadSessions();
   }, []);
 
+  // Follow the port the app configured (or uvicorn bound) instead of assuming 8000
+  const healthCheckUrl = tauriStatus?.health_check_url;
+  useEffect(() => {
+    if (!healthCheckUrl) return;
+    const origin = new URL(healthCheckUrl).origin;
+    fastapiClient.setBaseUrl(origin);
+    websocketClient.setBaseUrl(origin);
+    checkConnection();
+  }, [healthCheckUrl]);
+
   const checkConnection = async () => {
     const connected = await fastapiClient.healthCheck();
     setIsConnected(connected);
@@ -1,14 +1,34 @@
 'use client';
 
-import { useBunStatus, useMcpServer } from '@/hooks/useTauri';
+import { useBunStatus, useMcpServer, useServerRestarts } from '@/hooks/useTauri';
 
 export default function ServerStatus() {
   const { status: bunStatus, loading: bunLoading, error: bunError, installBun } = useBunStatus();
-  const { status: serverStatus, loading: serverLoading, error: serverError, installed: serverInstalled, installServer, startServer } = useMcpServer();
+  const { status: serverStatus, loading: serverLoading, error: serverError, installed: serverInstalled, installServer, startServer, stopServer } = useMcpServer();
+  const { notice: restartNotice, dismiss: dismissRestartNotice } = useServerRestarts();
 
   return (
     <div className="space-y-6 p-6 bg-white rounded-lg shadow-lg">
       <h2 className="text-2xl font-bold text-gray-800">Server Status</h2>
+
+      {restartNotice && (
+        <div
+          className={`flex items-center justify-between rounded-lg border p-3 text-sm ${
+            restartNotice.failed
+              ? 'bg-red-50 border-red-200 text-red-700'
+              : 'bg-yellow-50 border-yellow-200 text-yellow-800'
+          }`}
+        >
+          <span>
+            {restartNotice.failed
+              ? `${restartNotice.server} crashed and could not be restarted after ${restartNotice.attempt - 1} retries`
+              : `${restartNotice.server} crashed (exit code ${restartNotice.exit_code ?? 'unknown'}) and was restarted`}
+          </span>
+          <button onClick={dismissRestartNotice} className="ml-4 underline">
+            Dismiss
+          </button>
+        </div>
+      )}
       
       {/* Bun Status */}
       <div className="border rounded-lg p-4">
@@ -41,15 +61,20 @@ export default function ServerStatus() {
                 {bunStatus.path && (
                   <p className="text-sm text-gray-600">Path: {bunStatus.path}</p>
                 )}
+                {!bunStatus.compatible && (
+                  <p className="text-sm text-yellow-600">
+                    This Bun version is too old to run the MCP server
+                  </p>
+                )}
               </>
             )}
             
-            {!bunStatus.installed && (
+            {(!bunStatus.installed || !bunStatus.compatible) && (
               <button
                 onClick={installBun}
                 className="mt-2 px-4 py-2 bg-blue-500 text-white rounded hover:bg-blue-600 transition-colors"
               >
-                Install Bun
+                {bunStatus.installed ? 'Upgrade Bun' : 'Install Bun'}
               </button>
             )}
           </div>
@@ -113,6 +138,12 @@ export default function ServerStatus() {
                     {serverStatus.pid && (
                       <p className="text-sm text-gray-600">PID: {serverStatus.pid}</p>
                     )}
+                    <button
+                      onClick={stopServer}
+                      className="px-4 py-2 bg-red-500 text-white rounded hover:bg-red-600 transition-colors"
+                    >
+                      Stop MCP Server
+                    </button>
                   </>
                 )}
                 
@@ -1,18 +1,61 @@
 'use client';
 
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { useState, useEffect } from 'react';
 
+// Commands reject with a tagged AppError object ({ kind, message, ... })
+export interface AppError {
+  kind: string;
+  message: string;
+  [field: string]: unknown;
+}
+
+export function errorMessage(err: unknown): string {
+  if (typeof err === 'object' && err !== null && 'message' in err) {
+    return String((err as AppError).message);
+  }
+  return String(err);
+}
+
+export type ServerMode = 'development' | 'production';
+
 export interface BunStatus {
   installed: boolean;
   version?: string;
   path?: string;
+  // False when the installed Bun is older than the MCP server supports
+  compatible: boolean;
+}
+
+export interface PythonStatus {
+  installed: boolean;
+  version?: string;
+  path?: string;
 }
 
 export interface McpServerStatus {
+  // True once the server answers requests; port_open without running means it is still starting
   running: boolean;
+  port_open: boolean;
   port?: number;
+  socket_running: boolean;
+  owned_by_us: boolean;
   pid?: number;
+  mode: ServerMode;
+  config_dirty: boolean;
+  started_at?: number;
+  uptime_secs?: number;
+}
+
+// Payload of the `mcp-install-progress` event; file counts are only set while copying,
+// package counts and output lines only while bun install runs
+export interface InstallProgress {
+  phase: 'copying_files' | 'installing_dependencies' | 'done';
+  files_copied?: number;
+  total_files?: number;
+  packages?: number;
+  output_line?: string;
 }
 
 export function useBunStatus() {
@@ -27,7 +70,7 @@ export function useBunStatus() {
       const result = await invoke<BunStatus>('check_bun_installation');
       setStatus(result);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
     } finally {
       setLoading(false);
     }
@@ -40,7 +83,7 @@ export function useBunStatus() {
       await invoke<string>('install_bun');
       await checkBun();
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       setLoading(false);
     }
   };
@@ -63,6 +106,7 @@ export function useMcpServer() {
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
   const [installed, setInstalled] = useState<boolean | null>(null);
+  const [installProgress, setInstallProgress] = useState<InstallProgress | null>(null);
 
   const checkStatus = async () => {
     try {
@@ -71,7 +115,7 @@ export function useMcpServer() {
       const result = await invoke<McpServerStatus>('get_mcp_server_status');
       setStatus(result);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
     } finally {
       setLoading(false);
     }
@@ -82,7 +126,7 @@ export function useMcpServer() {
       const result = await invoke<boolean>('check_mcp_server_installation');
       setInstalled(result);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       setInstalled(false);
     }
   };
@@ -94,7 +138,7 @@ export function useMcpServer() {
       await invoke<string>('install_mcp_server');
       await checkInstallation(); // Recheck installation status
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
     } finally {
       setLoading(false);
     }
@@ -108,11 +152,32 @@ export function useMcpServer() {
       // Recheck status after starting
       setTimeout(checkStatus, 2000); // Wait a bit for server to start
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
+      setLoading(false);
+    }
+  };
+
+  const stopServer = async () => {
+    try {
+      setLoading(true);
+      setError(null);
+      await invoke<number[]>('stop_mcp_server');
+      await checkStatus();
+    } catch (err) {
+      setError(errorMessage(err));
       setLoading(false);
     }
   };
 
+  useEffect(() => {
+    const unlisten = listen<InstallProgress>('mcp-install-progress', (event) => {
+      setInstallProgress(event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   useEffect(() => {
     checkInstallation();
     checkStatus();
@@ -126,10 +191,12 @@ export function useMcpServer() {
     loading,
     error,
     installed,
+    installProgress,
     checkStatus,
     checkInstallation,
     installServer,
     startServer,
+    stopServer,
   };
 }
 
@@ -152,6 +219,56 @@ export interface FastAPIStatus {
   port?: number;
   pid?: number;
   health_check_url?: string;
+  restart_count: number;
+  last_exit_code?: number;
+  // Tail of stderr from the last run that exited
+  last_error?: string;
+  mode: ServerMode;
+  config_dirty: boolean;
+  started_at?: number;
+  uptime_secs?: number;
+  host: string;
+  // Set while FastAPI listens beyond loopback
+  exposure_warning?: string;
+}
+
+// Returned by get_system_status, which gathers every status in one call
+export interface SystemStatus {
+  bun: BunStatus;
+  python: PythonStatus;
+  mcp_server: McpServerStatus;
+  fastapi: FastAPIStatus;
+}
+
+// Result of check_fastapi_health; details is the full /health response body
+export interface HealthReport {
+  status: string;
+  latency_ms: number;
+  details: Record<string, unknown>;
+}
+
+// Payload of the `fastapi-ready` / `fastapi-failed` events
+export interface FastAPIReadinessEvent {
+  port: number;
+  pid: number;
+  error?: string;
+}
+
+// Payload of the `process://restarted` / `process://restart-failed` events
+export interface ServerRestartEvent {
+  server: string;
+  attempt: number;
+  exit_code?: number;
+}
+
+export interface ServerRestartNotice extends ServerRestartEvent {
+  failed: boolean;
+}
+
+// Payload of the `startup-phase` event
+export interface StartupPhaseEvent {
+  server: string;
+  phase: 'waiting_for_mcp' | 'starting';
 }
 
 export function useFastAPIStatus() {
@@ -169,7 +286,7 @@ export function useFastAPIStatus() {
       const result = await tauriInvoke<FastAPIStatus>('get_fastapi_server_status');
       setStatus(result);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
     } finally {
       setLoading(false);
     }
@@ -181,11 +298,10 @@ export function useFastAPIStatus() {
     try {
       setLoading(true);
       setError(null);
+      // Status is refreshed once the server reports fastapi-ready or fastapi-failed
       await tauriInvoke<string>('start_fastapi_server');
-      // Recheck status after starting
-      setTimeout(checkStatus, 3000);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       setLoading(false);
     }
   };
@@ -200,21 +316,37 @@ export function useFastAPIStatus() {
       // Recheck status after stopping
       setTimeout(checkStatus, 1000);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       setLoading(false);
     }
   };
 
-  const checkHealth = async () => {
-    if (!isTauri || !tauriInvoke) return false;
+  const checkHealth = async (): Promise<HealthReport | null> => {
+    if (!isTauri || !tauriInvoke) return null;
 
     try {
-      return await tauriInvoke<boolean>('check_fastapi_health');
+      return await tauriInvoke<HealthReport>('check_fastapi_health');
     } catch (err) {
-      return false;
+      return null;
     }
   };
 
+  useEffect(() => {
+    if (!isTauri) return;
+
+    const unlistenReady = listen<FastAPIReadinessEvent>('fastapi-ready', () => {
+      checkStatus();
+    });
+    const unlistenFailed = listen<FastAPIReadinessEvent>('fastapi-failed', (event) => {
+      setError(event.payload.error ?? 'FastAPI server failed to become healthy');
+      checkStatus();
+    });
+    return () => {
+      unlistenReady.then((fn) => fn());
+      unlistenFailed.then((fn) => fn());
+    };
+  }, [isTauri]);
+
   useEffect(() => {
     if (isTauri) {
       checkStatus();
@@ -234,3 +366,29 @@ export function useFastAPIStatus() {
     checkHealth,
   };
 }
+
+// Latest automatic restart (or given-up restart) reported by the crash supervisor
+export function useServerRestarts() {
+  const [notice, setNotice] = useState<ServerRestartNotice | null>(null);
+  const { isTauri } = useTauri();
+
+  useEffect(() => {
+    if (!isTauri) return;
+
+    const unlistenRestarted = listen<ServerRestartEvent>('process://restarted', (event) => {
+      setNotice({ ...event.payload, failed: false });
+    });
+    const unlistenFailed = listen<ServerRestartEvent>('process://restart-failed', (event) => {
+      setNotice({ ...event.payload, failed: true });
+    });
+    return () => {
+      unlistenRestarted.then((fn) => fn());
+      unlistenFailed.then((fn) => fn());
+    };
+  }, [isTauri]);
+
+  return {
+    notice,
+    dismiss: () => setNotice(null),
+  };
+}
@@ -5,6 +5,11 @@ class FastAPIClient {
     this.baseUrl = baseUrl;
   }
 
+  // Point the client at the server the app actually started, e.g. after fastapi_port changes
+  setBaseUrl(baseUrl: string) {
+    this.baseUrl = baseUrl;
+  }
+
   async query(query: string, sessionId?: string): Promise<any> {
     try {
       const response = await fetch(`${this.baseUrl}/query`, {
@@ -32,11 +32,22 @@ class WebSocketClient {
   private reconnectDelay = 1000;
   private pendingQueries = new Map<string, QueryOptions>();
   private isConnecting = false;
+  private baseUrl = 'ws://localhost:8000';
 
   constructor() {
     this.clientId = uuidv4();
   }
 
+  // Takes the FastAPI HTTP origin; an open connection to the old address is closed
+  setBaseUrl(httpUrl: string) {
+    const baseUrl = httpUrl.replace(/^http/, 'ws');
+    if (baseUrl === this.baseUrl) {
+      return;
+    }
+    this.baseUrl = baseUrl;
+    this.disconnect();
+  }
+
   async connect(): Promise<boolean> {
     if (this.isConnecting) {
       return new Promise((resolve) => {
@@ -61,7 +72,7 @@ class WebSocketClient {
 
     return new Promise((resolve, reject) => {
       try {
-        const wsUrl = `ws://localhost:8000/ws/${this.clientId}`;
+        const wsUrl = `${this.baseUrl}/ws/${this.clientId}`;
         this.ws = new WebSocket(wsUrl);
 
         this.ws.onopen = () => {